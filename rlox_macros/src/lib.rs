@@ -1,6 +1,28 @@
 use proc_macro::TokenStream;
 use quote::{format_ident, quote, quote_spanned};
-use syn::{parse_macro_input, Data, DeriveInput, Fields, FieldsUnnamed};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, FieldsUnnamed, ItemImpl};
+
+mod lox_class;
+
+/// Exposes the named fields of a struct as properties of a Lox host class.
+/// Fields marked `#[lox(skip)]` stay hidden, and `#[lox(rename = "name")]`
+/// changes the property name.
+#[proc_macro_derive(LoxClass, attributes(lox))]
+pub fn derive_lox_class(item: TokenStream) -> TokenStream {
+    lox_class::derive(parse_macro_input!(item as DeriveInput))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Exposes the methods of an `impl` block marked `#[lox]` as methods of a
+/// Lox host class, and the one marked `#[lox(constructor)]` as its
+/// initializer.
+#[proc_macro_attribute]
+pub fn lox_methods(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    lox_class::methods(parse_macro_input!(item as ItemImpl))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
 #[proc_macro_derive(Visitor)]
 #[allow(unused)]
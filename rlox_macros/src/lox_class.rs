@@ -0,0 +1,237 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, Data, DeriveInput, Error, Fields, FnArg, ImplItem, ImplItemFn, ItemImpl, LitStr,
+    Result, ReturnType, Type,
+};
+
+/// Implements `HostObject`, exposing every named field not marked
+/// `#[lox(skip)]` as a property.
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let ident = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            ident.span(),
+            "LoxClass can only be derived for structs.",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new(ident.span(), "LoxClass requires named fields."));
+    };
+
+    let mut getters = Vec::new();
+    let mut setters = Vec::new();
+    for field in &fields.named {
+        let options = Options::parse(&field.attrs)?;
+        if options.skip {
+            continue;
+        }
+        let field_ident = field.ident.as_ref().unwrap();
+        let name = options.rename.unwrap_or_else(|| field_ident.to_string());
+        getters.push(quote! {
+            #name => ::std::option::Option::Some(::rlox::convert::IntoLox::into_lox(
+                ::std::clone::Clone::clone(&self.#field_ident),
+            ))
+        });
+        setters.push(quote! {
+            #name => ::std::option::Option::Some(
+                ::rlox::convert::FromLox::from_lox(value).map(|value| self.#field_ident = value),
+            )
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::rlox::host::HostObject for #ident #ty_generics #where_clause {
+            fn get_property(&self, name: &str) -> ::std::option::Option<::rlox::Value> {
+                match name {
+                    #(#getters,)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+
+            fn set_property(
+                &mut self,
+                name: &str,
+                value: &::rlox::Value,
+            ) -> ::std::option::Option<
+                ::std::result::Result<(), ::rlox::error::ConversionError>,
+            > {
+                match name {
+                    #(#setters,)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn ::std::any::Any {
+                self
+            }
+        }
+    })
+}
+
+/// Implements `LoxClass` from the methods marked `#[lox]`, with the one
+/// marked `#[lox(constructor)]` becoming `init`. The markers are stripped
+/// from the returned impl block.
+pub fn methods(mut item: ItemImpl) -> Result<TokenStream> {
+    let self_ty = &item.self_ty;
+    let Type::Path(path) = self_ty.as_ref() else {
+        return Err(Error::new_spanned(self_ty, "Expected a struct name."));
+    };
+    let class_name = path.path.segments.last().unwrap().ident.to_string();
+
+    let mut methods = Vec::new();
+    for impl_item in &mut item.items {
+        let ImplItem::Fn(function) = impl_item else {
+            continue;
+        };
+        let Some(options) = take_options(&mut function.attrs)? else {
+            continue;
+        };
+        methods.push(native_method(function, options)?);
+    }
+
+    let (impl_generics, _, where_clause) = item.generics.split_for_impl();
+    Ok(quote! {
+        #item
+
+        impl #impl_generics ::rlox::host::LoxClass for #self_ty #where_clause {
+            const NAME: &'static str = #class_name;
+
+            fn methods() -> ::std::vec::Vec<::rlox::host::NativeMethod> {
+                ::std::vec![#(#methods),*]
+            }
+        }
+    })
+}
+
+fn native_method(function: &ImplItemFn, options: Options) -> Result<TokenStream> {
+    let ident = &function.sig.ident;
+    let mut receiver = None;
+    let mut arg_types = Vec::new();
+    for input in &function.sig.inputs {
+        match input {
+            FnArg::Receiver(r) if r.reference.is_some() => receiver = Some(r),
+            FnArg::Receiver(r) => {
+                return Err(Error::new_spanned(
+                    r,
+                    "Lox methods must take `&self` or `&mut self`.",
+                ))
+            }
+            FnArg::Typed(arg) => arg_types.push(&arg.ty),
+        }
+    }
+    let args = (0..arg_types.len())
+        .map(|i| format_ident!("arg{}", i))
+        .collect::<Vec<_>>();
+    let arity = args.len();
+    let convert_args = quote! {
+        let (#(#args,)*): (#(#arg_types,)*) = ::rlox::convert::FromArgs::from_args(args)?;
+    };
+
+    let body = if options.constructor {
+        if receiver.is_some() {
+            return Err(Error::new_spanned(
+                &function.sig,
+                "Constructors cannot take `self`.",
+            ));
+        }
+        if !matches!(&function.sig.output, ReturnType::Type(_, ty) if is_self(ty)) {
+            return Err(Error::new_spanned(
+                &function.sig,
+                "Constructors must return `Self`.",
+            ));
+        }
+        quote! {
+            #convert_args
+            instance.set_host(::std::boxed::Box::new(Self::#ident(#(#args),*)));
+            ::std::result::Result::Ok(::rlox::Value::Nil)
+        }
+    } else {
+        if receiver.is_none() {
+            return Err(Error::new_spanned(
+                &function.sig,
+                "Lox methods must take `&self` or `&mut self`.",
+            ));
+        }
+        quote! {
+            #convert_args
+            ::rlox::host::with_host(instance, |this: &mut Self| {
+                ::rlox::convert::IntoLoxResult::into_lox_result(this.#ident(#(#args),*))
+            })?
+        }
+    };
+
+    let name = match (options.rename, options.constructor) {
+        (_, true) => "init".to_string(),
+        (Some(name), false) => name,
+        (None, false) => ident.to_string(),
+    };
+    Ok(quote! {
+        ::rlox::host::NativeMethod::new(
+            #name,
+            #arity,
+            |instance: &::std::rc::Rc<::rlox::class::Instance>, args: &[::rlox::Value]| {
+                #body
+            },
+        )
+    })
+}
+
+fn is_self(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.is_ident("Self"))
+}
+
+#[derive(Default)]
+struct Options {
+    skip: bool,
+    constructor: bool,
+    rename: Option<String>,
+}
+
+impl Options {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut options = Options::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("lox")) {
+            options.parse_attr(attr)?;
+        }
+        Ok(options)
+    }
+
+    /// A bare `#[lox]` sets no options.
+    fn parse_attr(&mut self, attr: &Attribute) -> Result<()> {
+        if matches!(attr.meta, syn::Meta::Path(_)) {
+            return Ok(());
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                self.skip = true;
+            } else if meta.path.is_ident("constructor") {
+                self.constructor = true;
+            } else if meta.path.is_ident("rename") {
+                self.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else {
+                return Err(meta.error("Expected `skip`, `constructor` or `rename`."));
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Removes the `#[lox]` attributes from a method, returning their options if
+/// there were any.
+fn take_options(attrs: &mut Vec<Attribute>) -> Result<Option<Options>> {
+    let mut options = None;
+    let mut result = Ok(());
+    attrs.retain(|attr| {
+        if !attr.path().is_ident("lox") {
+            return true;
+        }
+        let options = options.get_or_insert_with(Options::default);
+        if let Err(e) = options.parse_attr(attr) {
+            result = Err(e);
+        }
+        false
+    });
+    result.map(|()| options)
+}
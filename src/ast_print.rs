@@ -1,25 +1,54 @@
-use crate::expression::{
-    Assign, Binary, Expression, ExpressionVisitor, Grouping, Literal, Unary, Var,
+use std::rc::Rc;
+
+use crate::{
+    expression::{
+        Assign, Binary, Call, Expression, ExpressionVisitor, Get, Grouping, Index, Interpolation,
+        List, Literal, LiteralValue, Map, Set, Super, This, Unary, Var,
+    },
+    statement::{
+        BlockStmt, BreakStmt, ClassDecl, ContinueStmt, ExprStmt, FunDecl, IfStmt, PrintStmt,
+        ReturnStmt, Stmt, StmtVisitor, VarDecl, WhileStmt,
+    },
 };
 
+/// Prints syntax trees as S-expressions.
 pub struct AstPrinter;
 impl AstPrinter {
     pub fn print(&mut self, expr: &Expression) -> String {
         expr.accept(self)
     }
+
+    pub fn print_stmt(&mut self, stmt: &Stmt) -> String {
+        stmt.accept(self)
+    }
+
+    fn print_block(&mut self, head: String, stmts: &[Stmt]) -> String {
+        let mut repr = format!("({}", head);
+        for stmt in stmts {
+            repr.push(' ');
+            repr.push_str(&self.print_stmt(stmt));
+        }
+        repr.push(')');
+        repr
+    }
+
+    fn print_function(&mut self, keyword: &str, inner: &FunDecl) -> String {
+        let head = format!("{} {} ({})", keyword, inner.name, inner.params.join(" "));
+        self.print_block(head, &inner.body)
+    }
 }
 
 impl ExpressionVisitor for AstPrinter {
     type Return = String;
     fn visit_literal(&mut self, inner: &Literal) -> Self::Return {
-        let repr = match inner {
-            Literal::Number(n) => &format!("{}", n),
-            Literal::String(s) => &format!(r#""{}""#, s),
-            Literal::True => "true",
-            Literal::False => "false",
-            Literal::Nil => "nil",
+        let repr = match &inner.value {
+            LiteralValue::Number(n) => &format!("{}", n),
+            LiteralValue::String(s) => &format!(r#""{}""#, s),
+            LiteralValue::True => "true",
+            LiteralValue::False => "false",
+            LiteralValue::Nil => "nil",
         };
-        format!("{}", repr)
+        repr.to_string()
     }
 
     fn visit_unary(&mut self, inner: &Unary) -> Self::Return {
@@ -46,31 +75,196 @@ impl ExpressionVisitor for AstPrinter {
     fn visit_assign(&mut self, inner: &Assign) -> Self::Return {
         format!(
             "(= {} {})",
-            self.print(inner.name.as_ref()),
+            self.print(inner.target.as_ref()),
             self.print(inner.value.as_ref())
         )
     }
+
+    fn visit_list(&mut self, inner: &List) -> Self::Return {
+        let mut repr = "(list".to_string();
+        for element in &inner.elements {
+            repr.push(' ');
+            repr.push_str(&self.print(element));
+        }
+        repr.push(')');
+        repr
+    }
+
+    fn visit_map(&mut self, inner: &Map) -> Self::Return {
+        let mut repr = "(map".to_string();
+        for (key, value) in &inner.entries {
+            repr.push_str(&format!(" ({} {})", self.print(key), self.print(value)));
+        }
+        repr.push(')');
+        repr
+    }
+
+    fn visit_interpolation(&mut self, inner: &Interpolation) -> Self::Return {
+        let mut repr = "(interpolate".to_string();
+        for part in &inner.parts {
+            repr.push(' ');
+            repr.push_str(&self.print(part));
+        }
+        repr.push(')');
+        repr
+    }
+
+    fn visit_index(&mut self, inner: &Index) -> Self::Return {
+        format!(
+            "([] {} {})",
+            self.print(inner.object.as_ref()),
+            self.print(inner.index.as_ref())
+        )
+    }
+
+    fn visit_call(&mut self, inner: &Call) -> Self::Return {
+        let mut repr = format!("(call {}", self.print(inner.callee.as_ref()));
+        for arg in &inner.arguments {
+            repr.push(' ');
+            repr.push_str(&self.print(arg));
+        }
+        repr.push(')');
+        repr
+    }
+
+    fn visit_get(&mut self, inner: &Get) -> Self::Return {
+        format!("(. {} {})", self.print(inner.object.as_ref()), inner.name)
+    }
+
+    fn visit_set(&mut self, inner: &Set) -> Self::Return {
+        format!(
+            "(= (. {} {}) {})",
+            self.print(inner.object.as_ref()),
+            inner.name,
+            self.print(inner.value.as_ref())
+        )
+    }
+
+    fn visit_this(&mut self, _inner: &This) -> Self::Return {
+        "this".to_string()
+    }
+
+    fn visit_super(&mut self, inner: &Super) -> Self::Return {
+        format!("(super {})", inner.method)
+    }
+}
+
+impl StmtVisitor for AstPrinter {
+    type Return = String;
+    fn visit_expr_stmt(&mut self, inner: &ExprStmt) -> Self::Return {
+        format!("(; {})", self.print(&inner.expr))
+    }
+
+    fn visit_print_stmt(&mut self, inner: &PrintStmt) -> Self::Return {
+        format!("(print {})", self.print(&inner.expr))
+    }
+
+    fn visit_var_decl(&mut self, inner: &VarDecl) -> Self::Return {
+        match &inner.initializer {
+            Some(initializer) => format!("(var-decl {} {})", inner.name, self.print(initializer)),
+            None => format!("(var-decl {})", inner.name),
+        }
+    }
+
+    fn visit_fun_decl(&mut self, inner: &Rc<FunDecl>) -> Self::Return {
+        self.print_function("fun", inner)
+    }
+
+    fn visit_class_decl(&mut self, inner: &ClassDecl) -> Self::Return {
+        let mut repr = format!("(class {}", inner.name);
+        if let Some(superclass) = &inner.superclass {
+            repr.push_str(&format!(" < {}", superclass.name));
+        }
+        for method in &inner.methods {
+            repr.push(' ');
+            repr.push_str(&self.print_function("method", method));
+        }
+        repr.push(')');
+        repr
+    }
+
+    fn visit_block_stmt(&mut self, inner: &BlockStmt) -> Self::Return {
+        self.print_block("block".to_string(), &inner.stmts)
+    }
+
+    fn visit_if_stmt(&mut self, inner: &IfStmt) -> Self::Return {
+        let mut repr = format!(
+            "(if {} {}",
+            self.print(&inner.condition),
+            self.print_stmt(&inner.then_branch)
+        );
+        if let Some(else_branch) = &inner.else_branch {
+            repr.push(' ');
+            repr.push_str(&self.print_stmt(else_branch));
+        }
+        repr.push(')');
+        repr
+    }
+
+    fn visit_while_stmt(&mut self, inner: &WhileStmt) -> Self::Return {
+        let label = match &inner.label {
+            Some(label) => format!("{}: ", label),
+            None => String::new(),
+        };
+        let mut repr = format!(
+            "(while {}{} {}",
+            label,
+            self.print(&inner.condition),
+            self.print_stmt(&inner.body)
+        );
+        if let Some(increment) = &inner.increment {
+            repr.push(' ');
+            repr.push_str(&self.print(increment));
+        }
+        repr.push(')');
+        repr
+    }
+
+    fn visit_return(&mut self, inner: &ReturnStmt) -> Self::Return {
+        match &inner.value {
+            Some(value) => format!("(return {})", self.print(value)),
+            None => "(return)".to_string(),
+        }
+    }
+
+    fn visit_break(&mut self, inner: &BreakStmt) -> Self::Return {
+        match &inner.label {
+            Some(label) => format!("(break {})", label),
+            None => "(break)".to_string(),
+        }
+    }
+
+    fn visit_continue(&mut self, inner: &ContinueStmt) -> Self::Return {
+        match &inner.label {
+            Some(label) => format!("(continue {})", label),
+            None => "(continue)".to_string(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::operator::*;
+    use crate::{operator::*, span::Span};
+
+    fn literal(value: LiteralValue) -> Literal {
+        Literal {
+            value,
+            span: Span::default(),
+        }
+    }
 
     #[test]
     fn test_literal() {
         let mut printer = AstPrinter;
-        let literal = Literal::String("print test".to_string());
-        assert_eq!(
-            r#""print test""#.to_string(),
-            printer.print(&literal.into())
-        );
+        let expr = literal(LiteralValue::String("print test".to_string()));
+        assert_eq!(r#""print test""#.to_string(), printer.print(&expr.into()));
 
-        let literal = Literal::True;
-        assert_eq!("true", printer.print(&literal.into()));
+        let expr = literal(LiteralValue::True);
+        assert_eq!("true", printer.print(&expr.into()));
 
-        let literal = Literal::Number(64.0);
-        assert_eq!("64", printer.print(&literal.into()));
+        let expr = literal(LiteralValue::Number(64.0));
+        assert_eq!("64", printer.print(&expr.into()));
     }
 
     #[test]
@@ -78,8 +272,9 @@ mod tests {
         let mut printer = AstPrinter;
         let binary = Binary {
             operator: BinaryOperator::Plus,
-            left: Box::new(Literal::Number(2.0).into()),
-            right: Box::new(Literal::Number(3.0).into()),
+            left: Box::new(literal(LiteralValue::Number(2.0)).into()),
+            right: Box::new(literal(LiteralValue::Number(3.0)).into()),
+            span: Span::default(),
         };
         assert_eq!("(+ 2 3)", printer.print(&binary.into()));
     }
@@ -92,16 +287,19 @@ mod tests {
             left: Box::new(
                 Unary {
                     operator: UnaryOperator::Minus,
-                    expr: Box::new(Literal::Number(123.0).into()),
+                    expr: Box::new(literal(LiteralValue::Number(123.0)).into()),
+                    span: Span::default(),
                 }
                 .into(),
             ),
             right: Box::new(
                 Grouping {
-                    expr: Box::new(Literal::Number(45.67).into()),
+                    expr: Box::new(literal(LiteralValue::Number(45.67)).into()),
+                    span: Span::default(),
                 }
                 .into(),
             ),
+            span: Span::default(),
         };
         assert_eq!("(* (- 123) (group 45.67))", printer.print(&expr.into()));
 
@@ -112,11 +310,13 @@ mod tests {
                     expr: Box::new(
                         Binary {
                             operator: BinaryOperator::Plus,
-                            left: Box::new(Literal::Number(2.0).into()),
-                            right: Box::new(Literal::Number(2.0).into()),
+                            left: Box::new(literal(LiteralValue::Number(2.0)).into()),
+                            right: Box::new(literal(LiteralValue::Number(2.0)).into()),
+                            span: Span::default(),
                         }
                         .into(),
                     ),
+                    span: Span::default(),
                 }
                 .into(),
             ),
@@ -125,24 +325,46 @@ mod tests {
                     expr: Box::new(
                         Binary {
                             operator: BinaryOperator::Plus,
-                            left: Box::new(Literal::Number(3.0).into()),
+                            left: Box::new(literal(LiteralValue::Number(3.0)).into()),
                             right: Box::new(
                                 Unary {
                                     operator: UnaryOperator::Minus,
-                                    expr: Box::new(Literal::Number(1.0).into()),
+                                    expr: Box::new(literal(LiteralValue::Number(1.0)).into()),
+                                    span: Span::default(),
                                 }
                                 .into(),
                             ),
+                            span: Span::default(),
                         }
                         .into(),
                     ),
+                    span: Span::default(),
                 }
                 .into(),
             ),
+            span: Span::default(),
         };
         assert_eq!(
             "(* (group (+ 2 2)) (group (+ 3 (- 1))))",
             printer.print(&expr.into())
         );
     }
+
+    #[test]
+    fn test_stmt() {
+        let source = "fun f(a, b) { if (a) return b; else { print a; } } var x = f(1, 2);";
+        let tokens = crate::Scanner::new(source.into()).scan_source().unwrap();
+        let stmts = crate::Parser::new(tokens).parse().unwrap();
+        let printed = stmts
+            .iter()
+            .map(|stmt| AstPrinter.print_stmt(stmt))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            printed,
+            [
+                "(fun f (a b) (if (var a) (return (var b)) (block (print (var a)))))",
+                "(var-decl x (call (var f) 1 2))",
+            ]
+        );
+    }
 }
@@ -0,0 +1,13 @@
+//! An alternative backend that compiles the AST into bytecode and runs it on
+//! a stack-based virtual machine, avoiding re-walking the tree in loops.
+
+mod chunk;
+mod compiler;
+mod disassembler;
+mod object;
+mod vm;
+
+pub use compiler::Compiler;
+pub use disassembler::disassemble;
+pub use object::{BoundMethod, Closure};
+pub use vm::Vm;
@@ -0,0 +1,210 @@
+use std::rc::Rc;
+
+use crate::span::Span;
+
+use super::object::Prototype;
+
+/// A single bytecode instruction. Operands follow the opcode byte inline;
+/// constant and name indices are two bytes wide (big-endian), slot and count
+/// operands are one byte.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpCode {
+    /// `index: u16` — push a constant.
+    Constant,
+    Nil,
+    True,
+    False,
+    Pop,
+    /// `slot: u8`
+    GetLocal,
+    /// `slot: u8`
+    SetLocal,
+    /// `name: u16`
+    GetGlobal,
+    /// `name: u16`
+    DefineGlobal,
+    /// `name: u16`
+    SetGlobal,
+    /// `index: u8`
+    GetUpvalue,
+    /// `index: u8`
+    SetUpvalue,
+    /// `name: u16`
+    GetProperty,
+    /// `name: u16`
+    SetProperty,
+    /// `name: u16` — pops the superclass and `this`, pushes the bound method.
+    GetSuper,
+    /// `count: u8` — pops the elements, pushes a list of them.
+    BuildList,
+    /// `count: u8` — pops that many keys and values, pushes a map of them.
+    BuildMap,
+    /// `count: u8` — pops the values, pushes the string of them all joined.
+    BuildString,
+    /// Pops the index and the list, pushes the element.
+    GetIndex,
+    /// Pops the value, the index and the list, pushes the value.
+    SetIndex,
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Not,
+    Negate,
+    Print,
+    /// `offset: u16` — jump forward.
+    Jump,
+    /// `offset: u16` — jump forward if the top of the stack is falsey.
+    JumpIfFalse,
+    /// `offset: u16` — jump backward.
+    Loop,
+    /// `args: u8`
+    Call,
+    /// `function: u16`, then `is_local: u8, index: u8` for each upvalue.
+    Closure,
+    CloseUpvalue,
+    Return,
+    /// `superclass_name: u16` — checks that the top of the stack is a class.
+    Inherit,
+    /// `name: u16, methods: u8` — pops method closures, pushes the class.
+    Class,
+    /// `name: u16, methods: u8` — like `Class`, inheriting from the value
+    /// below the methods.
+    Subclass,
+}
+
+impl OpCode {
+    pub(super) const ALL: [OpCode; 43] = [
+        OpCode::Constant,
+        OpCode::Nil,
+        OpCode::True,
+        OpCode::False,
+        OpCode::Pop,
+        OpCode::GetLocal,
+        OpCode::SetLocal,
+        OpCode::GetGlobal,
+        OpCode::DefineGlobal,
+        OpCode::SetGlobal,
+        OpCode::GetUpvalue,
+        OpCode::SetUpvalue,
+        OpCode::GetProperty,
+        OpCode::SetProperty,
+        OpCode::GetSuper,
+        OpCode::BuildList,
+        OpCode::BuildMap,
+        OpCode::BuildString,
+        OpCode::GetIndex,
+        OpCode::SetIndex,
+        OpCode::Equal,
+        OpCode::NotEqual,
+        OpCode::Greater,
+        OpCode::GreaterEqual,
+        OpCode::Less,
+        OpCode::LessEqual,
+        OpCode::Add,
+        OpCode::Subtract,
+        OpCode::Multiply,
+        OpCode::Divide,
+        OpCode::Not,
+        OpCode::Negate,
+        OpCode::Print,
+        OpCode::Jump,
+        OpCode::JumpIfFalse,
+        OpCode::Loop,
+        OpCode::Call,
+        OpCode::Closure,
+        OpCode::CloseUpvalue,
+        OpCode::Return,
+        OpCode::Inherit,
+        OpCode::Class,
+        OpCode::Subclass,
+    ];
+}
+
+impl TryFrom<u8> for OpCode {
+    type Error = u8;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::ALL.get(value as usize).copied().ok_or(value)
+    }
+}
+
+/// Values known at compile time, stored in a chunk's constant pool.
+#[derive(Debug, Clone)]
+pub enum Constant {
+    Number(f64),
+    String(String),
+    Function(Rc<Prototype>),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SpanRun {
+    span: Span,
+    count: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Chunk {
+    pub code: Vec<u8>,
+    pub constants: Vec<Constant>,
+    /// Run-length encoded source span of every byte in `code`.
+    spans: Vec<SpanRun>,
+}
+
+impl Chunk {
+    pub fn write(&mut self, byte: u8, span: Span) {
+        self.code.push(byte);
+        match self.spans.last_mut() {
+            Some(run) if run.span == span => run.count += 1,
+            _ => self.spans.push(SpanRun { span, count: 1 }),
+        }
+    }
+
+    pub fn write_u16(&mut self, value: u16, span: Span) {
+        let [hi, lo] = value.to_be_bytes();
+        self.write(hi, span);
+        self.write(lo, span);
+    }
+
+    pub fn read_u16(&self, offset: usize) -> u16 {
+        u16::from_be_bytes([self.code[offset], self.code[offset + 1]])
+    }
+
+    pub fn patch_u16(&mut self, offset: usize, value: u16) {
+        let [hi, lo] = value.to_be_bytes();
+        self.code[offset] = hi;
+        self.code[offset + 1] = lo;
+    }
+
+    /// Adds a constant, reusing an existing slot for identical numbers and
+    /// strings. Returns `None` once the pool is full.
+    pub fn add_constant(&mut self, constant: Constant) -> Option<u16> {
+        let existing = self.constants.iter().position(|c| match (c, &constant) {
+            (Constant::Number(a), Constant::Number(b)) => a.to_bits() == b.to_bits(),
+            (Constant::String(a), Constant::String(b)) => a == b,
+            _ => false,
+        });
+        let index = existing.unwrap_or_else(|| {
+            self.constants.push(constant);
+            self.constants.len() - 1
+        });
+        u16::try_from(index).ok()
+    }
+
+    pub fn span_at(&self, offset: usize) -> Span {
+        let mut end = 0;
+        for run in &self.spans {
+            end += run.count;
+            if offset < end {
+                return run.span;
+            }
+        }
+        self.spans.last().map_or_else(Span::default, |run| run.span)
+    }
+}
@@ -0,0 +1,777 @@
+use std::rc::Rc;
+
+use crate::{
+    error::{CompileError, CompileErrorType, LoxError},
+    expression::{
+        Assign, Binary, Call, Expression, ExpressionVisitor, Get, Grouping, Index, Interpolation,
+        List, Literal, LiteralValue, Map, Set, Super, This, Unary, Var,
+    },
+    operator::{BinaryOperator, UnaryOperator},
+    span::Span,
+    statement::{
+        BlockStmt, BreakStmt, ClassDecl, ContinueStmt, ExprStmt, FunDecl, IfStmt, PrintStmt,
+        ReturnStmt, Stmt, StmtVisitor, VarDecl, WhileStmt,
+    },
+};
+
+use super::{
+    chunk::{Constant, OpCode},
+    object::Prototype,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionKind {
+    Script,
+    Function,
+    Method,
+    Initializer,
+}
+
+struct Local {
+    name: String,
+    depth: usize,
+    is_captured: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct UpvalueRef {
+    index: u8,
+    is_local: bool,
+}
+
+/// A loop being compiled, with the jumps out of it that still need patching.
+struct Loop {
+    label: Option<String>,
+    /// Scope depth outside the loop body. Locals deeper than this are popped
+    /// before jumping.
+    depth: usize,
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+}
+
+/// Compilation state for the function currently being compiled.
+struct FunctionState {
+    proto: Prototype,
+    kind: FunctionKind,
+    locals: Vec<Local>,
+    upvalues: Vec<UpvalueRef>,
+    scope_depth: usize,
+    loops: Vec<Loop>,
+}
+
+impl FunctionState {
+    fn new(kind: FunctionKind, name: String, arity: usize) -> Self {
+        // Slot zero holds the callee, or the receiver for methods.
+        let receiver = match kind {
+            FunctionKind::Method | FunctionKind::Initializer => "this",
+            FunctionKind::Script | FunctionKind::Function => "",
+        };
+        Self {
+            proto: Prototype {
+                name,
+                arity,
+                ..Default::default()
+            },
+            kind,
+            locals: vec![Local {
+                name: receiver.into(),
+                depth: 0,
+                is_captured: false,
+            }],
+            upvalues: Vec::new(),
+            scope_depth: 0,
+            loops: Vec::new(),
+        }
+    }
+}
+
+enum Slot {
+    Local(u8),
+    Upvalue(u8),
+    Global(u16),
+}
+
+/// Compiles a resolved program into bytecode for the [`Vm`](super::Vm).
+pub struct Compiler {
+    functions: Vec<FunctionState>,
+    /// Span of the node being compiled, recorded against emitted bytecode.
+    span: Span,
+    errors: Vec<LoxError>,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self {
+            functions: vec![FunctionState::new(FunctionKind::Script, String::new(), 0)],
+            span: Span::new(0, 0, 1, 1),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl Compiler {
+    pub fn compile(mut self, stmts: &[Stmt]) -> Result<Rc<Prototype>, Vec<LoxError>> {
+        for stmt in stmts {
+            stmt.accept(&mut self);
+        }
+        self.emit_return();
+        let (script, _) = self.end_function();
+
+        if self.errors.is_empty() {
+            Ok(Rc::new(script))
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    fn current(&mut self) -> &mut FunctionState {
+        self.functions.last_mut().unwrap()
+    }
+
+    fn end_function(&mut self) -> (Prototype, Vec<UpvalueRef>) {
+        let mut state = self.functions.pop().unwrap();
+        state.proto.upvalue_count = state.upvalues.len();
+        (state.proto, state.upvalues)
+    }
+
+    fn error(&mut self, error_type: CompileErrorType) {
+        self.errors.push(
+            CompileError {
+                error_type,
+                span: self.span,
+            }
+            .into(),
+        );
+    }
+
+    fn emit_byte(&mut self, byte: u8) {
+        let span = self.span;
+        self.current().proto.chunk.write(byte, span);
+    }
+
+    fn emit_op(&mut self, op: OpCode) {
+        self.emit_byte(op as u8);
+    }
+
+    fn emit_u16(&mut self, value: u16) {
+        let span = self.span;
+        self.current().proto.chunk.write_u16(value, span);
+    }
+
+    fn emit_return(&mut self) {
+        match self.current().kind {
+            FunctionKind::Initializer => {
+                self.emit_op(OpCode::GetLocal);
+                self.emit_byte(0);
+            }
+            _ => self.emit_op(OpCode::Nil),
+        }
+        self.emit_op(OpCode::Return);
+    }
+
+    fn make_constant(&mut self, constant: Constant) -> u16 {
+        match self.current().proto.chunk.add_constant(constant) {
+            Some(index) => index,
+            None => {
+                self.error(CompileErrorType::TooManyConstants);
+                0
+            }
+        }
+    }
+
+    fn identifier_constant(&mut self, name: &str) -> u16 {
+        self.make_constant(Constant::String(name.into()))
+    }
+
+    fn emit_constant(&mut self, constant: Constant) {
+        let index = self.make_constant(constant);
+        self.emit_op(OpCode::Constant);
+        self.emit_u16(index);
+    }
+
+    /// Emits a forward jump with a placeholder offset, returning the offset
+    /// of the operand to patch once the target is known.
+    fn emit_jump(&mut self, op: OpCode) -> usize {
+        self.emit_op(op);
+        self.emit_u16(u16::MAX);
+        self.current().proto.chunk.code.len() - 2
+    }
+
+    fn patch_jump(&mut self, operand: usize) {
+        let jump = self.current().proto.chunk.code.len() - operand - 2;
+        let Ok(jump) = u16::try_from(jump) else {
+            self.error(CompileErrorType::JumpTooLarge);
+            return;
+        };
+        self.current().proto.chunk.patch_u16(operand, jump);
+    }
+
+    fn emit_loop(&mut self, loop_start: usize) {
+        self.emit_op(OpCode::Loop);
+        let offset = self.current().proto.chunk.code.len() - loop_start + 2;
+        let Ok(offset) = u16::try_from(offset) else {
+            self.error(CompileErrorType::JumpTooLarge);
+            return;
+        };
+        self.emit_u16(offset);
+    }
+
+    fn begin_scope(&mut self) {
+        self.current().scope_depth += 1;
+    }
+
+    fn end_scope(&mut self) {
+        let state = self.current();
+        state.scope_depth -= 1;
+        let depth = state.scope_depth;
+
+        while self
+            .current()
+            .locals
+            .last()
+            .is_some_and(|l| l.depth > depth)
+        {
+            let local = self.current().locals.pop().unwrap();
+            match local.is_captured {
+                true => self.emit_op(OpCode::CloseUpvalue),
+                false => self.emit_op(OpCode::Pop),
+            }
+        }
+    }
+
+    /// Emits the pops for locals deeper than `depth` without forgetting them,
+    /// for jumps that leave their scopes early.
+    fn discard_locals(&mut self, depth: usize) {
+        let captured = self
+            .current()
+            .locals
+            .iter()
+            .rev()
+            .take_while(|l| l.depth > depth)
+            .map(|l| l.is_captured)
+            .collect::<Vec<_>>();
+        for is_captured in captured {
+            match is_captured {
+                true => self.emit_op(OpCode::CloseUpvalue),
+                false => self.emit_op(OpCode::Pop),
+            }
+        }
+    }
+
+    /// The loop a `break` or `continue` with `label` refers to. The parser and
+    /// resolver make sure there is one.
+    fn target_loop(&mut self, label: &Option<String>) -> &mut Loop {
+        let loops = &mut self.current().loops;
+        match label {
+            Some(_) => loops.iter_mut().rev().find(|l| l.label == *label),
+            None => loops.last_mut(),
+        }
+        .unwrap()
+    }
+
+    /// Pops the locals of the scopes being left and emits a jump to be
+    /// patched once the target loop is compiled.
+    fn emit_loop_exit(&mut self, label: &Option<String>) -> usize {
+        let depth = self.target_loop(label).depth;
+        self.discard_locals(depth);
+        self.emit_jump(OpCode::Jump)
+    }
+
+    /// Declares a local in the current scope and returns its slot.
+    fn add_local(&mut self, name: &str) -> u8 {
+        let state = self.current();
+        let Ok(slot) = u8::try_from(state.locals.len()) else {
+            self.error(CompileErrorType::TooManyLocals);
+            return 0;
+        };
+        let depth = state.scope_depth;
+        state.locals.push(Local {
+            name: name.into(),
+            depth,
+            is_captured: false,
+        });
+        slot
+    }
+
+    fn resolve_local(&self, function: usize, name: &str) -> Option<u8> {
+        self.functions[function]
+            .locals
+            .iter()
+            .rposition(|l| l.name == name)
+            .map(|slot| slot as u8)
+    }
+
+    fn resolve_upvalue(&mut self, function: usize, name: &str) -> Option<u8> {
+        if function == 0 {
+            return None;
+        }
+        if let Some(slot) = self.resolve_local(function - 1, name) {
+            self.functions[function - 1].locals[slot as usize].is_captured = true;
+            return Some(self.add_upvalue(function, slot, true));
+        }
+        let index = self.resolve_upvalue(function - 1, name)?;
+        Some(self.add_upvalue(function, index, false))
+    }
+
+    fn add_upvalue(&mut self, function: usize, index: u8, is_local: bool) -> u8 {
+        let upvalue = UpvalueRef { index, is_local };
+        let upvalues = &mut self.functions[function].upvalues;
+        if let Some(existing) = upvalues.iter().position(|u| *u == upvalue) {
+            return existing as u8;
+        }
+        let Ok(count) = u8::try_from(upvalues.len()) else {
+            self.error(CompileErrorType::TooManyUpvalues);
+            return 0;
+        };
+        upvalues.push(upvalue);
+        count
+    }
+
+    fn resolve(&mut self, name: &str) -> Slot {
+        let function = self.functions.len() - 1;
+        if let Some(slot) = self.resolve_local(function, name) {
+            Slot::Local(slot)
+        } else if let Some(index) = self.resolve_upvalue(function, name) {
+            Slot::Upvalue(index)
+        } else {
+            Slot::Global(self.identifier_constant(name))
+        }
+    }
+
+    fn emit_get(&mut self, name: &str) {
+        match self.resolve(name) {
+            Slot::Local(slot) => {
+                self.emit_op(OpCode::GetLocal);
+                self.emit_byte(slot);
+            }
+            Slot::Upvalue(index) => {
+                self.emit_op(OpCode::GetUpvalue);
+                self.emit_byte(index);
+            }
+            Slot::Global(name) => {
+                self.emit_op(OpCode::GetGlobal);
+                self.emit_u16(name);
+            }
+        }
+    }
+
+    fn emit_set(&mut self, name: &str) {
+        match self.resolve(name) {
+            Slot::Local(slot) => {
+                self.emit_op(OpCode::SetLocal);
+                self.emit_byte(slot);
+            }
+            Slot::Upvalue(index) => {
+                self.emit_op(OpCode::SetUpvalue);
+                self.emit_byte(index);
+            }
+            Slot::Global(name) => {
+                self.emit_op(OpCode::SetGlobal);
+                self.emit_u16(name);
+            }
+        }
+    }
+
+    /// Binds the value on top of the stack to `name`: globals are defined by
+    /// name, locals simply stay in their stack slot.
+    fn define_variable(&mut self, name: &str) {
+        if self.current().scope_depth > 0 {
+            self.add_local(name);
+        } else {
+            let name = self.identifier_constant(name);
+            self.emit_op(OpCode::DefineGlobal);
+            self.emit_u16(name);
+        }
+    }
+
+    fn compile_function(&mut self, declaration: &FunDecl, kind: FunctionKind) {
+        self.span = declaration.span;
+        let state = FunctionState::new(kind, declaration.name.clone(), declaration.params.len());
+        self.functions.push(state);
+
+        self.begin_scope();
+        for param in &declaration.params {
+            self.add_local(param);
+        }
+        for stmt in &declaration.body {
+            stmt.accept(&mut *self);
+        }
+        self.emit_return();
+
+        let (proto, upvalues) = self.end_function();
+        let index = self.make_constant(Constant::Function(Rc::new(proto)));
+        self.emit_op(OpCode::Closure);
+        self.emit_u16(index);
+        for upvalue in upvalues {
+            self.emit_byte(upvalue.is_local as u8);
+            self.emit_byte(upvalue.index);
+        }
+    }
+
+    fn compile_expr(&mut self, expr: &Expression) {
+        expr.accept(self)
+    }
+}
+
+impl ExpressionVisitor for Compiler {
+    type Return = ();
+    fn visit_literal(&mut self, inner: &Literal) -> Self::Return {
+        self.span = inner.span;
+        match &inner.value {
+            LiteralValue::Number(n) => self.emit_constant(Constant::Number(*n)),
+            LiteralValue::String(s) => self.emit_constant(Constant::String(s.clone())),
+            LiteralValue::True => self.emit_op(OpCode::True),
+            LiteralValue::False => self.emit_op(OpCode::False),
+            LiteralValue::Nil => self.emit_op(OpCode::Nil),
+        }
+    }
+
+    fn visit_unary(&mut self, inner: &Unary) -> Self::Return {
+        self.compile_expr(&inner.expr);
+        match inner.operator {
+            UnaryOperator::Minus => self.emit_op(OpCode::Negate),
+            UnaryOperator::Not => self.emit_op(OpCode::Not),
+        }
+    }
+
+    fn visit_binary(&mut self, inner: &Binary) -> Self::Return {
+        self.compile_expr(&inner.left);
+
+        // short circuit logical operators
+        match inner.operator {
+            BinaryOperator::And => {
+                let end_jump = self.emit_jump(OpCode::JumpIfFalse);
+                self.emit_op(OpCode::Pop);
+                self.compile_expr(&inner.right);
+                self.patch_jump(end_jump);
+                return;
+            }
+            BinaryOperator::Or => {
+                let else_jump = self.emit_jump(OpCode::JumpIfFalse);
+                let end_jump = self.emit_jump(OpCode::Jump);
+                self.patch_jump(else_jump);
+                self.emit_op(OpCode::Pop);
+                self.compile_expr(&inner.right);
+                self.patch_jump(end_jump);
+                return;
+            }
+            _ => (),
+        }
+
+        self.compile_expr(&inner.right);
+        let op = match inner.operator {
+            BinaryOperator::Minus => OpCode::Subtract,
+            BinaryOperator::Plus => OpCode::Add,
+            BinaryOperator::Mult => OpCode::Multiply,
+            BinaryOperator::Div => OpCode::Divide,
+            BinaryOperator::EqualEqual => OpCode::Equal,
+            BinaryOperator::NotEqual => OpCode::NotEqual,
+            BinaryOperator::Less => OpCode::Less,
+            BinaryOperator::LessEqual => OpCode::LessEqual,
+            BinaryOperator::Greater => OpCode::Greater,
+            BinaryOperator::GreaterEqual => OpCode::GreaterEqual,
+            BinaryOperator::Equal | BinaryOperator::And | BinaryOperator::Or => {
+                unreachable!("Handled by the parser or above")
+            }
+        };
+        self.emit_op(op);
+    }
+
+    fn visit_grouping(&mut self, inner: &Grouping) -> Self::Return {
+        self.compile_expr(&inner.expr);
+    }
+
+    fn visit_var(&mut self, inner: &Var) -> Self::Return {
+        self.span = inner.span;
+        self.emit_get(&inner.name);
+    }
+
+    fn visit_assign(&mut self, inner: &Assign) -> Self::Return {
+        match inner.target.as_ref() {
+            Expression::Var(var) => {
+                self.compile_expr(&inner.value);
+                self.span = var.span;
+                self.emit_set(&var.name);
+            }
+            Expression::Index(target) => {
+                self.compile_expr(&target.object);
+                self.compile_expr(&target.index);
+                self.compile_expr(&inner.value);
+                self.span = target.span;
+                self.emit_op(OpCode::SetIndex);
+            }
+            _ => self.error(CompileErrorType::InvalidAssignmentTarget),
+        }
+    }
+
+    fn visit_list(&mut self, inner: &List) -> Self::Return {
+        for element in &inner.elements {
+            self.compile_expr(element);
+        }
+        self.span = inner.span;
+        let Ok(count) = u8::try_from(inner.elements.len()) else {
+            self.error(CompileErrorType::TooManyElements);
+            return;
+        };
+        self.emit_op(OpCode::BuildList);
+        self.emit_byte(count);
+    }
+
+    fn visit_map(&mut self, inner: &Map) -> Self::Return {
+        for (key, value) in &inner.entries {
+            self.compile_expr(key);
+            self.compile_expr(value);
+        }
+        self.span = inner.span;
+        let Ok(count) = u8::try_from(inner.entries.len()) else {
+            self.error(CompileErrorType::TooManyElements);
+            return;
+        };
+        self.emit_op(OpCode::BuildMap);
+        self.emit_byte(count);
+    }
+
+    fn visit_interpolation(&mut self, inner: &Interpolation) -> Self::Return {
+        for part in &inner.parts {
+            self.compile_expr(part);
+        }
+        self.span = inner.span;
+        let Ok(count) = u8::try_from(inner.parts.len()) else {
+            self.error(CompileErrorType::TooManyElements);
+            return;
+        };
+        self.emit_op(OpCode::BuildString);
+        self.emit_byte(count);
+    }
+
+    fn visit_index(&mut self, inner: &Index) -> Self::Return {
+        self.compile_expr(&inner.object);
+        self.compile_expr(&inner.index);
+        self.span = inner.span;
+        self.emit_op(OpCode::GetIndex);
+    }
+
+    fn visit_call(&mut self, inner: &Call) -> Self::Return {
+        self.compile_expr(&inner.callee);
+        for arg in &inner.arguments {
+            self.compile_expr(arg);
+        }
+        self.span = inner.span;
+        let Ok(count) = u8::try_from(inner.arguments.len()) else {
+            self.error(CompileErrorType::TooManyArguments);
+            return;
+        };
+        self.emit_op(OpCode::Call);
+        self.emit_byte(count);
+    }
+
+    fn visit_get(&mut self, inner: &Get) -> Self::Return {
+        self.compile_expr(&inner.object);
+        self.span = inner.span;
+        let name = self.identifier_constant(&inner.name);
+        self.emit_op(OpCode::GetProperty);
+        self.emit_u16(name);
+    }
+
+    fn visit_set(&mut self, inner: &Set) -> Self::Return {
+        self.compile_expr(&inner.object);
+        self.compile_expr(&inner.value);
+        self.span = inner.span;
+        let name = self.identifier_constant(&inner.name);
+        self.emit_op(OpCode::SetProperty);
+        self.emit_u16(name);
+    }
+
+    fn visit_this(&mut self, inner: &This) -> Self::Return {
+        self.span = inner.span;
+        self.emit_get("this");
+    }
+
+    fn visit_super(&mut self, inner: &Super) -> Self::Return {
+        self.span = inner.span;
+        self.emit_get("this");
+        self.emit_get("super");
+        let name = self.identifier_constant(&inner.method);
+        self.emit_op(OpCode::GetSuper);
+        self.emit_u16(name);
+    }
+}
+
+impl StmtVisitor for Compiler {
+    type Return = ();
+    fn visit_expr_stmt(&mut self, inner: &ExprStmt) -> Self::Return {
+        self.compile_expr(&inner.expr);
+        self.emit_op(OpCode::Pop);
+    }
+
+    fn visit_print_stmt(&mut self, inner: &PrintStmt) -> Self::Return {
+        self.compile_expr(&inner.expr);
+        self.emit_op(OpCode::Print);
+    }
+
+    fn visit_var_decl(&mut self, inner: &VarDecl) -> Self::Return {
+        self.span = inner.span;
+        match &inner.initializer {
+            Some(e) => self.compile_expr(e),
+            None => self.emit_op(OpCode::Nil),
+        }
+        self.define_variable(&inner.name);
+    }
+
+    fn visit_fun_decl(&mut self, inner: &Rc<FunDecl>) -> Self::Return {
+        // Locals are declared first so the function can refer to itself.
+        if self.current().scope_depth > 0 {
+            self.add_local(&inner.name);
+            self.compile_function(inner, FunctionKind::Function);
+        } else {
+            self.compile_function(inner, FunctionKind::Function);
+            self.define_variable(&inner.name);
+        }
+    }
+
+    fn visit_class_decl(&mut self, inner: &ClassDecl) -> Self::Return {
+        self.span = inner.span;
+        let name = self.identifier_constant(&inner.name);
+
+        // A local class gets its slot up front so methods can capture it.
+        let local_slot = (self.current().scope_depth > 0).then(|| {
+            self.emit_op(OpCode::Nil);
+            self.add_local(&inner.name)
+        });
+
+        if let Some(superclass) = &inner.superclass {
+            self.span = superclass.span;
+            self.visit_var(superclass);
+            let superclass_name = self.identifier_constant(&superclass.name);
+            self.emit_op(OpCode::Inherit);
+            self.emit_u16(superclass_name);
+
+            // The superclass stays on the stack as the local `super`.
+            self.begin_scope();
+            self.add_local("super");
+        }
+
+        for method in &inner.methods {
+            let kind = match method.name.as_str() {
+                "init" => FunctionKind::Initializer,
+                _ => FunctionKind::Method,
+            };
+            self.compile_function(method, kind);
+        }
+
+        self.span = inner.span;
+        let Ok(count) = u8::try_from(inner.methods.len()) else {
+            self.error(CompileErrorType::TooManyMethods);
+            return;
+        };
+        match inner.superclass {
+            Some(_) => self.emit_op(OpCode::Subclass),
+            None => self.emit_op(OpCode::Class),
+        }
+        self.emit_u16(name);
+        self.emit_byte(count);
+
+        match local_slot {
+            Some(slot) => {
+                self.emit_op(OpCode::SetLocal);
+                self.emit_byte(slot);
+                self.emit_op(OpCode::Pop);
+            }
+            None => {
+                self.emit_op(OpCode::DefineGlobal);
+                self.emit_u16(name);
+            }
+        }
+
+        if inner.superclass.is_some() {
+            self.end_scope();
+        }
+    }
+
+    fn visit_block_stmt(&mut self, inner: &BlockStmt) -> Self::Return {
+        self.begin_scope();
+        for stmt in &inner.stmts {
+            stmt.accept(&mut *self);
+        }
+        self.end_scope();
+    }
+
+    fn visit_if_stmt(&mut self, inner: &IfStmt) -> Self::Return {
+        self.compile_expr(&inner.condition);
+        let then_jump = self.emit_jump(OpCode::JumpIfFalse);
+        self.emit_op(OpCode::Pop);
+        inner.then_branch.accept(&mut *self);
+        let else_jump = self.emit_jump(OpCode::Jump);
+
+        self.patch_jump(then_jump);
+        self.emit_op(OpCode::Pop);
+        if let Some(else_branch) = &inner.else_branch {
+            else_branch.accept(&mut *self);
+        }
+        self.patch_jump(else_jump);
+    }
+
+    fn visit_while_stmt(&mut self, inner: &WhileStmt) -> Self::Return {
+        let loop_start = self.current().proto.chunk.code.len();
+        self.compile_expr(&inner.condition);
+        let exit_jump = self.emit_jump(OpCode::JumpIfFalse);
+        self.emit_op(OpCode::Pop);
+
+        let depth = self.current().scope_depth;
+        self.current().loops.push(Loop {
+            label: inner.label.clone(),
+            depth,
+            breaks: Vec::new(),
+            continues: Vec::new(),
+        });
+        inner.body.accept(&mut *self);
+        let Loop {
+            breaks, continues, ..
+        } = self.current().loops.pop().unwrap();
+
+        for jump in continues {
+            self.patch_jump(jump);
+        }
+        if let Some(increment) = &inner.increment {
+            self.compile_expr(increment);
+            self.emit_op(OpCode::Pop);
+        }
+        self.emit_loop(loop_start);
+
+        self.patch_jump(exit_jump);
+        self.emit_op(OpCode::Pop);
+        for jump in breaks {
+            self.patch_jump(jump);
+        }
+    }
+
+    fn visit_return(&mut self, inner: &ReturnStmt) -> Self::Return {
+        self.span = inner.span;
+        match (&inner.value, self.current().kind) {
+            // Initializers always return `this`, matching the tree-walker.
+            (_, FunctionKind::Initializer) => {
+                if let Some(value) = &inner.value {
+                    self.compile_expr(value);
+                    self.emit_op(OpCode::Pop);
+                }
+                self.emit_op(OpCode::GetLocal);
+                self.emit_byte(0);
+            }
+            (Some(value), _) => self.compile_expr(value),
+            (None, _) => self.emit_op(OpCode::Nil),
+        }
+        self.emit_op(OpCode::Return);
+    }
+
+    fn visit_break(&mut self, inner: &BreakStmt) -> Self::Return {
+        self.span = inner.span;
+        let jump = self.emit_loop_exit(&inner.label);
+        self.target_loop(&inner.label).breaks.push(jump);
+    }
+
+    fn visit_continue(&mut self, inner: &ContinueStmt) -> Self::Return {
+        self.span = inner.span;
+        let jump = self.emit_loop_exit(&inner.label);
+        self.target_loop(&inner.label).continues.push(jump);
+    }
+}
@@ -0,0 +1,173 @@
+use std::fmt::{Display, Write};
+
+use super::{
+    chunk::{Chunk, Constant, OpCode},
+    object::Prototype,
+};
+
+/// Renders the bytecode of `script` and of every function nested in it, one
+/// instruction per line with its offset, source line, operands and resolved
+/// constants.
+pub fn disassemble(script: &Prototype) -> String {
+    let mut out = String::new();
+    disassemble_function(script, &mut out).expect("Writing to a String cannot fail");
+    out
+}
+
+fn disassemble_function(function: &Prototype, out: &mut String) -> std::fmt::Result {
+    writeln!(out, "== {} ==", FunctionName(function))?;
+    let chunk = &function.chunk;
+    let mut offset = 0;
+    while offset < chunk.code.len() {
+        offset = disassemble_instruction(chunk, offset, out)?;
+    }
+
+    for constant in &chunk.constants {
+        if let Constant::Function(nested) = constant {
+            writeln!(out)?;
+            disassemble_function(nested, out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the instruction at `offset` and returns the offset of the next one.
+fn disassemble_instruction(
+    chunk: &Chunk,
+    offset: usize,
+    out: &mut String,
+) -> Result<usize, std::fmt::Error> {
+    write!(out, "{:04} ", offset)?;
+    let line_num = chunk.span_at(offset).line;
+    if offset > 0 && line_num == chunk.span_at(offset - 1).line {
+        write!(out, "   | ")?;
+    } else {
+        write!(out, "{:4} ", line_num)?;
+    }
+
+    let Ok(op) = OpCode::try_from(chunk.code[offset]) else {
+        writeln!(out, "Unknown opcode {}", chunk.code[offset])?;
+        return Ok(offset + 1);
+    };
+    let name = format!("{:?}", op);
+
+    match op {
+        OpCode::Constant
+        | OpCode::GetGlobal
+        | OpCode::DefineGlobal
+        | OpCode::SetGlobal
+        | OpCode::GetProperty
+        | OpCode::SetProperty
+        | OpCode::GetSuper
+        | OpCode::Inherit => {
+            let index = chunk.read_u16(offset + 1);
+            let constant = &chunk.constants[index as usize];
+            writeln!(out, "{:<16} {:4} {}", name, index, constant)?;
+            Ok(offset + 3)
+        }
+        OpCode::GetLocal
+        | OpCode::SetLocal
+        | OpCode::GetUpvalue
+        | OpCode::SetUpvalue
+        | OpCode::BuildList
+        | OpCode::BuildMap
+        | OpCode::BuildString
+        | OpCode::Call => {
+            writeln!(out, "{:<16} {:4}", name, chunk.code[offset + 1])?;
+            Ok(offset + 2)
+        }
+        OpCode::Jump | OpCode::JumpIfFalse | OpCode::Loop => {
+            let jump = chunk.read_u16(offset + 1) as usize;
+            let target = match op {
+                OpCode::Loop => offset + 3 - jump,
+                _ => offset + 3 + jump,
+            };
+            writeln!(out, "{:<16} {:4} -> {:04}", name, jump, target)?;
+            Ok(offset + 3)
+        }
+        OpCode::Class | OpCode::Subclass => {
+            let index = chunk.read_u16(offset + 1);
+            let methods = chunk.code[offset + 3];
+            let constant = &chunk.constants[index as usize];
+            writeln!(
+                out,
+                "{:<16} {:4} {} ({} methods)",
+                name, index, constant, methods
+            )?;
+            Ok(offset + 4)
+        }
+        OpCode::Closure => {
+            let index = chunk.read_u16(offset + 1);
+            let constant = &chunk.constants[index as usize];
+            writeln!(out, "{:<16} {:4} {}", name, index, constant)?;
+
+            let Constant::Function(function) = constant else {
+                return Ok(offset + 3);
+            };
+            let mut offset = offset + 3;
+            for _ in 0..function.upvalue_count {
+                let kind = match chunk.code[offset] {
+                    1 => "local",
+                    _ => "upvalue",
+                };
+                writeln!(
+                    out,
+                    "{:04}    | {:>21} {}",
+                    offset,
+                    kind,
+                    chunk.code[offset + 1]
+                )?;
+                offset += 2;
+            }
+            Ok(offset)
+        }
+        OpCode::Nil
+        | OpCode::True
+        | OpCode::False
+        | OpCode::Pop
+        | OpCode::GetIndex
+        | OpCode::SetIndex
+        | OpCode::Equal
+        | OpCode::NotEqual
+        | OpCode::Greater
+        | OpCode::GreaterEqual
+        | OpCode::Less
+        | OpCode::LessEqual
+        | OpCode::Add
+        | OpCode::Subtract
+        | OpCode::Multiply
+        | OpCode::Divide
+        | OpCode::Not
+        | OpCode::Negate
+        | OpCode::Print
+        | OpCode::CloseUpvalue
+        | OpCode::Return => {
+            writeln!(out, "{}", name)?;
+            Ok(offset + 1)
+        }
+    }
+}
+
+struct FunctionName<'a>(&'a Prototype);
+
+impl Display for FunctionName<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.name.as_str() {
+            "" => write!(f, "<script>"),
+            name => write!(f, "<fn {}>", name),
+        }
+    }
+}
+
+impl Display for Constant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Constant::Number(n) => write!(f, "{}", n),
+            Constant::String(s) => write!(f, r#""{}""#, s),
+            Constant::Function(function) => write!(f, "{}", FunctionName(function)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
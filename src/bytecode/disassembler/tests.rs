@@ -0,0 +1,74 @@
+use insta::{assert_snapshot, with_settings};
+
+use super::*;
+use crate::{bytecode::Compiler, parser::Parser, resolver::Resolver, scanner::Scanner};
+
+fn disassemble_source(source: &'static str) -> String {
+    let tokens = Scanner::new(source.to_string()).scan_source().unwrap();
+    let stmts = Parser::new(tokens).parse().unwrap();
+    Resolver::default().resolve(&stmts).unwrap();
+    let script = Compiler::default().compile(&stmts).unwrap();
+    disassemble(&script)
+}
+
+macro_rules! snapshot_test {
+    ($name:ident, $( $source:literal ),*) => {
+        #[test]
+        fn $name() {
+            $(
+                let source: &'static str = $source;
+                with_settings!({ description => source }, {
+                    assert_snapshot!(disassemble_source(source));
+                });
+            )*
+        }
+    };
+}
+
+snapshot_test!(
+    test_disassemble_globals,
+    r#"
+var greeting = "Hello";
+print greeting + ", world!";
+greeting = -1.5 * 2;
+"#
+);
+snapshot_test!(
+    test_disassemble_control_flow,
+    r#"
+var x = 0;
+while (x < 3 and true) {
+    if (x == 1 or false) print x; else x = x + 1;
+    x = x + 1;
+}
+"#
+);
+snapshot_test!(
+    test_disassemble_closure,
+    r#"
+fun makeCounter() {
+    var i = 0;
+    fun count() {
+        i = i + 1;
+        return i;
+    }
+    return count;
+}
+print makeCounter()();
+"#
+);
+snapshot_test!(
+    test_disassemble_class,
+    r#"
+class Animal {
+    init(name) {
+        this.name = name;
+    }
+}
+class Dog < Animal {
+    speak() {
+        return super.init(this.name);
+    }
+}
+"#
+);
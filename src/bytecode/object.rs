@@ -0,0 +1,80 @@
+use std::{cell::RefCell, cmp::Ordering, fmt::Debug, rc::Rc};
+
+use crate::value::Value;
+
+use super::chunk::Chunk;
+
+/// A compiled function, before it has captured any upvalues.
+#[derive(Debug, Default)]
+pub struct Prototype {
+    /// Empty for the top-level script.
+    pub name: String,
+    pub arity: usize,
+    pub upvalue_count: usize,
+    pub chunk: Chunk,
+}
+
+/// A captured variable. Open upvalues still point at a live stack slot;
+/// they are closed over (moved into the heap) when that slot goes away.
+#[derive(Debug)]
+pub enum Upvalue {
+    Open(usize),
+    Closed(Value),
+}
+
+pub struct Closure {
+    pub function: Rc<Prototype>,
+    pub upvalues: Vec<Rc<RefCell<Upvalue>>>,
+}
+
+impl Closure {
+    pub fn name(&self) -> &str {
+        &self.function.name
+    }
+
+    pub fn arity(&self) -> usize {
+        self.function.arity
+    }
+}
+
+// Upvalues may refer back to the closure, so they are left out to avoid
+// recursing forever.
+impl Debug for Closure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Closure")
+            .field("name", &self.name())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A method closure paired with the instance it was accessed on.
+#[derive(Debug)]
+pub struct BoundMethod {
+    pub receiver: Value,
+    pub method: Rc<Closure>,
+}
+
+// Closures and bound methods compare by identity.
+impl PartialEq for Closure {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl PartialOrd for Closure {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self == other).then_some(Ordering::Equal)
+    }
+}
+
+impl PartialEq for BoundMethod {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl PartialOrd for BoundMethod {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self == other).then_some(Ordering::Equal)
+    }
+}
@@ -0,0 +1,536 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    io::{self, BufRead, Write},
+    rc::Rc,
+};
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    class::{Class, Instance, Method},
+    error::{Result, RuntimeError, RuntimeErrorType},
+    native::{self, Input},
+    span::Span,
+    value::{MapKey, Value},
+};
+
+use super::{
+    chunk::{Constant, OpCode},
+    object::{BoundMethod, Closure, Prototype, Upvalue},
+};
+
+const FRAMES_MAX: usize = 1024;
+
+struct CallFrame {
+    closure: Rc<Closure>,
+    ip: usize,
+    /// Stack index of slot zero for this call.
+    base: usize,
+}
+
+impl CallFrame {
+    fn read_byte(&mut self) -> u8 {
+        let byte = self.closure.function.chunk.code[self.ip];
+        self.ip += 1;
+        byte
+    }
+
+    fn read_u16(&mut self) -> u16 {
+        let value = self.closure.function.chunk.read_u16(self.ip);
+        self.ip += 2;
+        value
+    }
+
+    fn read_constant(&mut self) -> &Constant {
+        let index = self.read_u16() as usize;
+        &self.closure.function.chunk.constants[index]
+    }
+
+    fn read_name(&mut self) -> &str {
+        match self.read_constant() {
+            Constant::String(name) => name,
+            constant => panic!("Expected a name constant, found {:?}", constant),
+        }
+    }
+}
+
+/// A stack-based virtual machine executing chunks produced by the
+/// [`Compiler`](super::Compiler). Globals persist between calls to
+/// [`Vm::interpret`].
+pub struct Vm {
+    stack: Vec<Value>,
+    frames: Vec<CallFrame>,
+    globals: FxHashMap<String, Value>,
+    /// Upvalues still pointing into the stack.
+    open_upvalues: Vec<Rc<RefCell<Upvalue>>>,
+    stdout: Box<dyn Write>,
+    stdin: Input,
+}
+
+impl Default for Vm {
+    fn default() -> Self {
+        let stdin = Input::default();
+        let globals = native::defaults()
+            .into_iter()
+            .chain([native::read_line(Rc::clone(&stdin))])
+            .map(|native| (native.name.clone(), Value::Native(Rc::new(native))))
+            .collect();
+        Self {
+            stack: Vec::new(),
+            frames: Vec::new(),
+            globals,
+            open_upvalues: Vec::new(),
+            stdout: Box::new(io::stdout()),
+            stdin,
+        }
+    }
+}
+
+impl Vm {
+    /// Sends the output of `print` to `stdout`.
+    pub fn with_stdout(mut self, stdout: impl Write + 'static) -> Self {
+        self.stdout = Box::new(stdout);
+        self
+    }
+
+    /// Makes `readLine()` read from `stdin`.
+    pub fn with_stdin(self, stdin: impl BufRead + 'static) -> Self {
+        *self.stdin.borrow_mut() = Some(Box::new(stdin));
+        self
+    }
+
+    /// Every global variable, sorted by name.
+    pub fn globals(&self) -> Vec<(String, Value)> {
+        let mut globals = self
+            .globals
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        globals.sort_by(|(a, _), (b, _)| a.cmp(b));
+        globals
+    }
+
+    pub fn interpret(&mut self, script: Rc<Prototype>) -> Result<()> {
+        let closure = Rc::new(Closure {
+            function: script,
+            upvalues: Vec::new(),
+        });
+        self.stack.push(Value::Closure(Rc::clone(&closure)));
+        let frame = CallFrame {
+            closure,
+            ip: 0,
+            base: 0,
+        };
+
+        let result = self.run(frame);
+        if result.is_err() {
+            self.stack.clear();
+            self.frames.clear();
+            self.open_upvalues.clear();
+        }
+        result
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("Stack should not be empty")
+    }
+
+    fn peek(&self, distance: usize) -> &Value {
+        &self.stack[self.stack.len() - 1 - distance]
+    }
+
+    fn run(&mut self, mut frame: CallFrame) -> Result<()> {
+        loop {
+            let offset = frame.ip;
+            let span = frame.closure.function.chunk.span_at(offset);
+            let runtime_error = |error_type| RuntimeError { error_type, span };
+
+            let op = OpCode::try_from(frame.read_byte())
+                .unwrap_or_else(|byte| panic!("unknown opcode {} at offset {}", byte, offset));
+            match op {
+                OpCode::Constant => {
+                    let value = match frame.read_constant() {
+                        Constant::Number(n) => Value::Number(*n),
+                        Constant::String(s) => Value::String(s.clone()),
+                        Constant::Function(_) => unreachable!("functions are loaded as closures"),
+                    };
+                    self.stack.push(value);
+                }
+                OpCode::Nil => self.stack.push(Value::Nil),
+                OpCode::True => self.stack.push(Value::Bool(true)),
+                OpCode::False => self.stack.push(Value::Bool(false)),
+                OpCode::Pop => {
+                    self.pop();
+                }
+                OpCode::GetLocal => {
+                    let slot = frame.read_byte() as usize;
+                    self.stack.push(self.stack[frame.base + slot].clone());
+                }
+                OpCode::SetLocal => {
+                    let slot = frame.read_byte() as usize;
+                    self.stack[frame.base + slot] = self.peek(0).clone();
+                }
+                OpCode::GetGlobal => {
+                    let name = frame.read_name();
+                    let value = self.globals.get(name).cloned().ok_or_else(|| {
+                        runtime_error(RuntimeErrorType::UndefinedVariable { name: name.into() })
+                    })?;
+                    self.stack.push(value);
+                }
+                OpCode::DefineGlobal => {
+                    let name = frame.read_name().to_string();
+                    let value = self.pop();
+                    self.globals.insert(name, value);
+                }
+                OpCode::SetGlobal => {
+                    let name = frame.read_name();
+                    let value = self.stack.last().unwrap().clone();
+                    match self.globals.get_mut(name) {
+                        Some(slot) => *slot = value,
+                        None => {
+                            return Err(runtime_error(RuntimeErrorType::UndefinedVariable {
+                                name: name.into(),
+                            })
+                            .into())
+                        }
+                    }
+                }
+                OpCode::GetUpvalue => {
+                    let index = frame.read_byte() as usize;
+                    let value = match &*frame.closure.upvalues[index].borrow() {
+                        Upvalue::Open(slot) => self.stack[*slot].clone(),
+                        Upvalue::Closed(value) => value.clone(),
+                    };
+                    self.stack.push(value);
+                }
+                OpCode::SetUpvalue => {
+                    let index = frame.read_byte() as usize;
+                    let value = self.peek(0).clone();
+                    match &mut *frame.closure.upvalues[index].borrow_mut() {
+                        Upvalue::Open(slot) => self.stack[*slot] = value,
+                        Upvalue::Closed(closed) => *closed = value,
+                    }
+                }
+                OpCode::GetProperty => {
+                    let name = frame.read_name();
+                    let Value::Instance(instance) = self.peek(0).clone() else {
+                        return Err(runtime_error(RuntimeErrorType::NotAnInstance).into());
+                    };
+                    let value = match instance.get_field(name) {
+                        Some(value) => value,
+                        None => bind_method(
+                            &instance.class,
+                            name,
+                            Value::Instance(Rc::clone(&instance)),
+                        )
+                        .ok_or_else(|| {
+                            runtime_error(RuntimeErrorType::UndefinedProperty { name: name.into() })
+                        })?,
+                    };
+                    self.pop();
+                    self.stack.push(value);
+                }
+                OpCode::SetProperty => {
+                    let name = frame.read_name();
+                    let value = self.pop();
+                    let Value::Instance(instance) = self.pop() else {
+                        return Err(runtime_error(RuntimeErrorType::NotAnInstance).into());
+                    };
+                    instance
+                        .set_field(name, value.clone())
+                        .map_err(|e| runtime_error(e.into()))?;
+                    self.stack.push(value);
+                }
+                OpCode::GetSuper => {
+                    let name = frame.read_name();
+                    let Value::Class(superclass) = self.pop() else {
+                        unreachable!("superclasses are checked when inheriting");
+                    };
+                    let receiver = self.pop();
+                    let method = bind_method(&superclass, name, receiver).ok_or_else(|| {
+                        runtime_error(RuntimeErrorType::UndefinedProperty { name: name.into() })
+                    })?;
+                    self.stack.push(method);
+                }
+                OpCode::BuildList => {
+                    let count = frame.read_byte() as usize;
+                    let elements = self.stack.split_off(self.stack.len() - count);
+                    self.stack.push(Value::list(elements));
+                }
+                OpCode::BuildMap => {
+                    let count = frame.read_byte() as usize;
+                    let mut entries = BTreeMap::new();
+                    let stack = self.stack.split_off(self.stack.len() - 2 * count);
+                    for pair in stack.chunks_exact(2) {
+                        let key = MapKey::try_from(&pair[0]).map_err(runtime_error)?;
+                        entries.insert(key, pair[1].clone());
+                    }
+                    self.stack.push(Value::map(entries));
+                }
+                OpCode::BuildString => {
+                    let count = frame.read_byte() as usize;
+                    let parts = self.stack.split_off(self.stack.len() - count);
+                    let string = parts.iter().map(Value::to_string).collect();
+                    self.stack.push(Value::String(string));
+                }
+                OpCode::GetIndex => {
+                    let index = self.pop();
+                    let list = self.pop();
+                    let value = list.get_index(&index).map_err(runtime_error)?;
+                    self.stack.push(value);
+                }
+                OpCode::SetIndex => {
+                    let value = self.pop();
+                    let index = self.pop();
+                    let list = self.pop();
+                    list.set_index(&index, value.clone())
+                        .map_err(runtime_error)?;
+                    self.stack.push(value);
+                }
+                OpCode::Equal => self
+                    .binary_op(|l, r| Ok(Value::Bool(l == r)))
+                    .map_err(runtime_error)?,
+                OpCode::NotEqual => self
+                    .binary_op(|l, r| Ok(Value::Bool(l != r)))
+                    .map_err(runtime_error)?,
+                OpCode::Greater => self
+                    .binary_op(|l, r| l.compare(&r, Value::gt))
+                    .map_err(runtime_error)?,
+                OpCode::GreaterEqual => self
+                    .binary_op(|l, r| l.compare(&r, Value::ge))
+                    .map_err(runtime_error)?,
+                OpCode::Less => self
+                    .binary_op(|l, r| l.compare(&r, Value::lt))
+                    .map_err(runtime_error)?,
+                OpCode::LessEqual => self
+                    .binary_op(|l, r| l.compare(&r, Value::le))
+                    .map_err(runtime_error)?,
+                OpCode::Add => self.binary_op(|l, r| l + r).map_err(runtime_error)?,
+                OpCode::Subtract => self.binary_op(|l, r| l - r).map_err(runtime_error)?,
+                OpCode::Multiply => self.binary_op(|l, r| l * r).map_err(runtime_error)?,
+                OpCode::Divide => self.binary_op(|l, r| l / r).map_err(runtime_error)?,
+                OpCode::Not => {
+                    let value = self.pop();
+                    self.stack.push(Value::Bool(!value.is_truthy()));
+                }
+                OpCode::Negate => {
+                    let value = self.pop();
+                    let Value::Number(n) = value else {
+                        return Err(runtime_error(RuntimeErrorType::TypeMismatch {
+                            expected: "number",
+                            actual: value.type_name(),
+                        })
+                        .into());
+                    };
+                    self.stack.push(Value::Number(-n));
+                }
+                OpCode::Print => {
+                    let value = self.pop();
+                    writeln!(self.stdout, "{}", value).map_err(|e| runtime_error(e.into()))?;
+                }
+                OpCode::Jump => {
+                    let jump = frame.read_u16() as usize;
+                    frame.ip += jump;
+                }
+                OpCode::JumpIfFalse => {
+                    let jump = frame.read_u16() as usize;
+                    if !self.peek(0).is_truthy() {
+                        frame.ip += jump;
+                    }
+                }
+                OpCode::Loop => {
+                    let jump = frame.read_u16() as usize;
+                    frame.ip -= jump;
+                }
+                OpCode::Call => {
+                    let arg_count = frame.read_byte() as usize;
+                    if let Some(callee_frame) = self.call_value(arg_count, span)? {
+                        if self.frames.len() >= FRAMES_MAX {
+                            return Err(runtime_error(RuntimeErrorType::StackOverflow).into());
+                        }
+                        self.frames
+                            .push(std::mem::replace(&mut frame, callee_frame));
+                    }
+                }
+                OpCode::Closure => {
+                    let Constant::Function(function) = frame.read_constant().clone() else {
+                        unreachable!("closures are built from function constants");
+                    };
+                    let mut upvalues = Vec::with_capacity(function.upvalue_count);
+                    for _ in 0..function.upvalue_count {
+                        let is_local = frame.read_byte() == 1;
+                        let index = frame.read_byte() as usize;
+                        let upvalue = match is_local {
+                            true => self.capture_upvalue(frame.base + index),
+                            false => Rc::clone(&frame.closure.upvalues[index]),
+                        };
+                        upvalues.push(upvalue);
+                    }
+                    let closure = Closure { function, upvalues };
+                    self.stack.push(Value::Closure(Rc::new(closure)));
+                }
+                OpCode::CloseUpvalue => {
+                    self.close_upvalues(self.stack.len() - 1);
+                    self.pop();
+                }
+                OpCode::Return => {
+                    let result = self.pop();
+                    self.close_upvalues(frame.base);
+                    self.stack.truncate(frame.base);
+                    match self.frames.pop() {
+                        Some(caller) => frame = caller,
+                        None => return Ok(()),
+                    }
+                    self.stack.push(result);
+                }
+                OpCode::Inherit => {
+                    let name = frame.read_name();
+                    if !matches!(self.peek(0), Value::Class(_)) {
+                        return Err(runtime_error(RuntimeErrorType::SuperclassNotClass {
+                            name: name.into(),
+                        })
+                        .into());
+                    }
+                }
+                OpCode::Class | OpCode::Subclass => {
+                    let name = frame.read_name().to_string();
+                    let method_count = frame.read_byte() as usize;
+                    let methods = self
+                        .stack
+                        .split_off(self.stack.len() - method_count)
+                        .into_iter()
+                        .map(|method| match method {
+                            Value::Closure(closure) => {
+                                (closure.name().to_string(), Method::Closure(closure))
+                            }
+                            value => panic!("Expected a method closure, found {:?}", value),
+                        })
+                        .collect::<FxHashMap<_, _>>();
+                    let superclass = match (op, self.peek(0)) {
+                        (OpCode::Subclass, Value::Class(superclass)) => Some(Rc::clone(superclass)),
+                        _ => None,
+                    };
+                    let class = Class::new(name, superclass, methods);
+                    self.stack.push(Value::Class(Rc::new(class)));
+                }
+            }
+        }
+    }
+
+    fn binary_op(
+        &mut self,
+        op: impl FnOnce(Value, Value) -> Result<Value, RuntimeErrorType>,
+    ) -> Result<(), RuntimeErrorType> {
+        let right = self.pop();
+        let left = self.pop();
+        self.stack.push(op(left, right)?);
+        Ok(())
+    }
+
+    /// Calls the value below the arguments on the stack. Returns the frame to
+    /// continue in when the callee is a Lox function.
+    fn call_value(&mut self, arg_count: usize, span: Span) -> Result<Option<CallFrame>> {
+        let base = self.stack.len() - arg_count - 1;
+        let closure = match self.stack[base].clone() {
+            Value::Closure(closure) => closure,
+            Value::BoundMethod(bound) => {
+                self.stack[base] = bound.receiver.clone();
+                Rc::clone(&bound.method)
+            }
+            Value::Class(class) => {
+                let instance = Rc::new(Instance::new(Rc::clone(&class)));
+                self.stack[base] = Value::Instance(instance);
+                match class.find_method("init") {
+                    Some(Method::Closure(init)) => Rc::clone(init),
+                    Some(Method::Function(_) | Method::Native(_)) => {
+                        unreachable!("Tree-walker classes never reach the VM")
+                    }
+                    None => {
+                        check_arity(0, arg_count, span)?;
+                        return Ok(None);
+                    }
+                }
+            }
+            Value::Native(native) => {
+                check_arity(native.arity, arg_count, span)?;
+                let result = native
+                    .call(&self.stack[base + 1..])
+                    .map_err(|error_type| RuntimeError { error_type, span })?;
+                self.stack.truncate(base);
+                self.stack.push(result);
+                return Ok(None);
+            }
+            _ => {
+                return Err(RuntimeError {
+                    error_type: RuntimeErrorType::NotCallable,
+                    span,
+                }
+                .into())
+            }
+        };
+
+        check_arity(closure.arity(), arg_count, span)?;
+        Ok(Some(CallFrame {
+            closure,
+            ip: 0,
+            base,
+        }))
+    }
+
+    fn capture_upvalue(&mut self, slot: usize) -> Rc<RefCell<Upvalue>> {
+        let existing = self
+            .open_upvalues
+            .iter()
+            .find(|u| matches!(*u.borrow(), Upvalue::Open(s) if s == slot));
+        if let Some(upvalue) = existing {
+            return Rc::clone(upvalue);
+        }
+
+        let upvalue = Rc::new(RefCell::new(Upvalue::Open(slot)));
+        self.open_upvalues.push(Rc::clone(&upvalue));
+        upvalue
+    }
+
+    /// Moves every captured stack slot at or above `from` into its upvalue.
+    fn close_upvalues(&mut self, from: usize) {
+        let stack = &self.stack;
+        self.open_upvalues.retain(|upvalue| {
+            let mut upvalue = upvalue.borrow_mut();
+            match *upvalue {
+                Upvalue::Open(slot) if slot >= from => {
+                    *upvalue = Upvalue::Closed(stack[slot].clone());
+                    false
+                }
+                _ => true,
+            }
+        });
+    }
+}
+
+/// Looks up a method declared by the VM and binds it to `receiver`.
+fn bind_method(class: &Class, name: &str, receiver: Value) -> Option<Value> {
+    match class.find_method(name)? {
+        Method::Closure(method) => Some(Value::BoundMethod(Rc::new(BoundMethod {
+            receiver,
+            method: Rc::clone(method),
+        }))),
+        Method::Function(_) | Method::Native(_) => {
+            unreachable!("Tree-walker classes never reach the VM")
+        }
+    }
+}
+
+fn check_arity(expected: usize, found: usize, span: Span) -> Result<()> {
+    if expected != found {
+        return Err(RuntimeError {
+            error_type: RuntimeErrorType::ArityMismatch { expected, found },
+            span,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
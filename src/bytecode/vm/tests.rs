@@ -0,0 +1,544 @@
+use std::io;
+
+use crate::{
+    bytecode::Compiler, error::LoxError, parser::Parser, resolver::Resolver, scanner::Scanner,
+};
+
+use super::*;
+
+impl Vm {
+    fn get_var(&self, name: &str) -> Value {
+        self.globals.get(name).cloned().unwrap()
+    }
+}
+
+/// A sink that stays readable after the VM takes ownership of it.
+#[derive(Clone, Default)]
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn run(source: &'static str, vm: &mut Vm) -> Result<()> {
+    let tokens = Scanner::new(source.into()).scan_source().unwrap();
+    let stmts = Parser::new(tokens).parse().unwrap();
+    Resolver::default().resolve(&stmts).unwrap();
+    let script = Compiler::default().compile(&stmts).unwrap();
+    vm.interpret(script)
+}
+
+fn interpret_stmts(source: &'static str, vm: &mut Vm) {
+    run(source, vm).unwrap();
+}
+
+fn evaluate_expr(source: &'static str) -> Value {
+    let mut vm = Vm::default();
+    let tokens = Scanner::new(format!("var result = {}", source)).scan_source();
+    let stmts = Parser::new(tokens.unwrap()).parse().unwrap();
+    let script = Compiler::default().compile(&stmts).unwrap();
+    vm.interpret(script).unwrap();
+    vm.get_var("result")
+}
+
+#[test]
+fn test_opcode_round_trip() {
+    for (byte, op) in OpCode::ALL.iter().enumerate() {
+        assert_eq!(*op as u8, byte as u8);
+        assert_eq!(OpCode::try_from(byte as u8), Ok(*op));
+    }
+}
+
+#[test]
+fn test_evaluate() {
+    assert_eq!(evaluate_expr("2 + 3 * 5;"), Value::Number(17.0));
+    assert_eq!(
+        evaluate_expr(r#" "hello" * 3 ;"#),
+        Value::String("hellohellohello".into())
+    );
+    assert_eq!(evaluate_expr("12 + 3 == 3 * 5;"), Value::Bool(true));
+    assert_eq!(
+        evaluate_expr(r#" "hello" * 3 == "hellohellohello" ;"#),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        evaluate_expr("2 + 2 == 4 and true and 3 <= 4;"),
+        Value::Bool(true)
+    );
+    assert_eq!(evaluate_expr(r#" "three" == 3 ;"#), Value::Bool(false));
+    assert_eq!(evaluate_expr("nil or false;"), Value::Bool(false));
+    assert_eq!(evaluate_expr("1 and nil;"), Value::Nil);
+}
+
+#[test]
+#[should_panic]
+fn test_error() {
+    evaluate_expr("true + false;");
+}
+
+#[test]
+fn test_vm_expr_stmt() {
+    let mut vm = Vm::default();
+    interpret_stmts("var x = 5 + 5; x = x + 1;", &mut vm);
+    assert_eq!(vm.get_var("x"), Value::Number(11.0));
+}
+
+#[test]
+fn test_vm_if_stmt() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        var x = 5;
+        if (x > 10)
+            x = x * 2;
+        else
+            x = x * 3;
+    "#,
+        &mut vm,
+    );
+    assert_eq!(vm.get_var("x"), Value::Number(15.0));
+}
+
+#[test]
+fn test_vm_while_stmt() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        var x = 0;
+        while (x < 10) {
+            x = x + 1;
+        }
+    "#,
+        &mut vm,
+    );
+    assert_eq!(vm.get_var("x"), Value::Number(10.0));
+}
+
+#[test]
+fn test_vm_break_continue() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        var sum = 0;
+        var closures = [];
+        for (var i = 0; i < 10; i = i + 1) {
+            var skipped = i;
+            fun get() { return skipped; }
+            if (i == 1) continue;
+            push(closures, get);
+            if (i == 3) break;
+            sum = sum + i;
+        }
+        var first = closures[0]();
+        var last = closures[2]();
+        var after = 0;
+        while (true) {
+            var local = after;
+            after = local + 1;
+            if (after == 5) break;
+        }
+    "#,
+        &mut vm,
+    );
+    assert_eq!(vm.get_var("sum"), Value::Number(2.0));
+    assert_eq!(vm.get_var("first"), Value::Number(0.0));
+    assert_eq!(vm.get_var("last"), Value::Number(3.0));
+    assert_eq!(vm.get_var("after"), Value::Number(5.0));
+}
+
+#[test]
+fn test_vm_labeled_loops() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        var seen = [];
+        outer: for (var i = 0; i < 3; i = i + 1) {
+            var j = 0;
+            while (true) {
+                var cell = i * 10 + j;
+                fun get() { return cell; }
+                j = j + 1;
+                if (j == 2) continue outer;
+                if (cell == 20) break outer;
+                push(seen, get);
+            }
+        }
+        var count = len(seen);
+        var last = seen[count - 1]();
+    "#,
+        &mut vm,
+    );
+    assert_eq!(vm.get_var("count"), Value::Number(2.0));
+    assert_eq!(vm.get_var("last"), Value::Number(10.0));
+}
+
+#[test]
+fn test_vm_fun_decl() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        fun fib(n) {
+            if (n <= 1) return n;
+            return fib(n - 2) + fib(n - 1);
+        }
+        var x = fib(10);
+    "#,
+        &mut vm,
+    );
+    assert_eq!(vm.get_var("x"), Value::Number(55.0));
+}
+
+#[test]
+fn test_vm_return_unwinds_scopes() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        var x = "global";
+        fun find(limit) {
+            var i = 0;
+            while (true) {
+                var x = i * 2;
+                if (x >= limit) {
+                    return i;
+                }
+                i = i + 1;
+            }
+        }
+        var y = find(7);
+    "#,
+        &mut vm,
+    );
+    assert_eq!(vm.get_var("y"), Value::Number(4.0));
+    assert_eq!(vm.get_var("x"), Value::String("global".into()));
+}
+
+#[test]
+#[should_panic]
+fn test_vm_arity_mismatch() {
+    let mut vm = Vm::default();
+    interpret_stmts("fun f(a, b) { return a + b; } f(1);", &mut vm);
+}
+
+#[test]
+fn test_vm_closure() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        fun makeCounter() {
+            var i = 0;
+            fun count() {
+                i = i + 1;
+                return i;
+            }
+            return count;
+        }
+        var a = makeCounter();
+        var b = makeCounter();
+        a();
+        a();
+        var x = a();
+        var y = b();
+    "#,
+        &mut vm,
+    );
+    assert_eq!(vm.get_var("x"), Value::Number(3.0));
+    assert_eq!(vm.get_var("y"), Value::Number(1.0));
+}
+
+#[test]
+fn test_vm_class() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        class Counter {
+            init(start) {
+                this.count = start;
+            }
+
+            increment() {
+                this.count = this.count + 1;
+                return this;
+            }
+        }
+        var counter = Counter(10);
+        counter.increment().increment();
+        var increment = counter.increment;
+        increment();
+        var x = counter.count;
+        counter.label = "clicks";
+        var y = counter.label;
+    "#,
+        &mut vm,
+    );
+    assert_eq!(vm.get_var("x"), Value::Number(13.0));
+    assert_eq!(vm.get_var("y"), Value::String("clicks".into()));
+}
+
+#[test]
+#[should_panic]
+fn test_vm_undefined_property() {
+    let mut vm = Vm::default();
+    interpret_stmts("class A {} var a = A(); a.missing;", &mut vm);
+}
+
+#[test]
+fn test_vm_inheritance() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        class Animal {
+            init(name) {
+                this.name = name;
+            }
+
+            speak() {
+                return this.name + " makes a sound";
+            }
+
+            describe() {
+                return "animal";
+            }
+        }
+        class Dog < Animal {
+            speak() {
+                return super.speak() + " (woof)";
+            }
+        }
+        class Puppy < Dog {}
+        var p = Puppy("Rex");
+        var x = p.speak();
+        var y = p.describe();
+    "#,
+        &mut vm,
+    );
+    assert_eq!(
+        vm.get_var("x"),
+        Value::String("Rex makes a sound (woof)".into())
+    );
+    assert_eq!(vm.get_var("y"), Value::String("animal".into()));
+}
+
+#[test]
+fn test_vm_inheritance_errors() {
+    let mut vm = Vm::default();
+    assert_eq!(
+        run("var A = 1;\nclass B < A {}", &mut vm)
+            .unwrap_err()
+            .to_string(),
+        "line 2:11: Superclass `A` must be a class"
+    );
+}
+
+#[test]
+fn test_vm_resolved_closure_binding() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        var a = "global";
+        var first;
+        var second;
+        {
+            fun showA() {
+                return a;
+            }
+            first = showA();
+            var a = "block";
+            second = showA();
+        }
+    "#,
+        &mut vm,
+    );
+    assert_eq!(vm.get_var("first"), Value::String("global".into()));
+    assert_eq!(vm.get_var("second"), Value::String("global".into()));
+}
+
+#[test]
+fn test_vm_closed_upvalues() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        var getters;
+        {
+            var shared = 1;
+            fun get() {
+                return shared;
+            }
+            fun set(value) {
+                shared = value;
+            }
+            set(42);
+            getters = get;
+        }
+        var x = getters();
+    "#,
+        &mut vm,
+    );
+    assert_eq!(vm.get_var("x"), Value::Number(42.0));
+}
+
+#[test]
+fn test_vm_recovers_after_error() {
+    let mut vm = Vm::default();
+    assert!(run("var x = 1; fun f() { return x + nil; } f();", &mut vm).is_err());
+    interpret_stmts("x = x + 1;", &mut vm);
+    assert_eq!(vm.get_var("x"), Value::Number(2.0));
+}
+
+#[test]
+fn test_vm_lists() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        var xs = [1, 2, [3]];
+        var alias = xs;
+        alias[0] = xs[2][0] + 1;
+        push(xs, "end");
+        var copy = slice(xs, 0, len(xs));
+        var last = pop(xs);
+    "#,
+        &mut vm,
+    );
+    assert_eq!(vm.get_var("xs").to_string(), "[4, 2, [3]]");
+    assert_eq!(vm.get_var("copy").to_string(), "[4, 2, [3], \"end\"]");
+    assert_eq!(vm.get_var("last"), Value::String("end".into()));
+
+    let error = run("xs[3];", &mut vm).unwrap_err();
+    assert_eq!(
+        error,
+        LoxError::from(RuntimeError {
+            error_type: RuntimeErrorType::IndexOutOfRange { index: 3, len: 3 },
+            span: Span::new(0, 5, 1, 1),
+        })
+    );
+}
+
+#[test]
+fn test_vm_self_containing_collections() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        var xs = [];
+        push(xs, xs);
+        var m = {};
+        m[nil] = m;
+        var ys = [];
+        push(ys, ys);
+        var n = {};
+        n[nil] = n;
+        var same = xs == xs and m == m and xs == ys and m == n;
+        var shown = "${xs} ${m}";
+    "#,
+        &mut vm,
+    );
+    assert_eq!(vm.get_var("same"), Value::Bool(true));
+    assert_eq!(
+        vm.get_var("shown"),
+        Value::String("[[...]] {nil: {...}}".into())
+    );
+}
+
+#[test]
+fn test_vm_compare_lists() {
+    let mut vm = Vm::default();
+    interpret_stmts("var xs = [1];\npush(xs, xs);", &mut vm);
+    for source in ["xs < xs;", "[1, 2] < [1, 3];", "1 >= [1];"] {
+        let error = run(source, &mut vm).unwrap_err().to_string();
+        assert!(error.ends_with("Expected number, found list"), "{}", error);
+    }
+    interpret_stmts("var ordered = \"a\" < \"b\" and 1 <= 1;", &mut vm);
+    assert_eq!(vm.get_var("ordered"), Value::Bool(true));
+}
+
+#[test]
+fn test_vm_interpolation() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        var name = "Ada";
+        var items = [1, 2];
+        var greeting = "Hello ${name}, you have ${len(items) + 1} items${"!"}";
+    "#,
+        &mut vm,
+    );
+    assert_eq!(
+        vm.get_var("greeting"),
+        Value::String("Hello Ada, you have 3 items!".into())
+    );
+}
+
+#[test]
+fn test_vm_maps() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        var m = {"b": 2, "a": [1]};
+        var alias = m;
+        alias["c"] = m["a"][0] + m["b"];
+        m[0 / 0] = nil;
+        var found = has(m, 0 / 0);
+        var removed = remove(m, "b");
+        var names = keys(m);
+    "#,
+        &mut vm,
+    );
+    assert_eq!(
+        vm.get_var("m").to_string(),
+        "{NaN: nil, \"a\": [1], \"c\": 3}"
+    );
+    assert_eq!(vm.get_var("found"), Value::Bool(true));
+    assert_eq!(vm.get_var("removed"), Value::Number(2.0));
+    assert_eq!(vm.get_var("names").to_string(), "[NaN, \"a\", \"c\"]");
+
+    let error = run("m[\"b\"];", &mut vm).unwrap_err();
+    assert_eq!(
+        error,
+        LoxError::from(RuntimeError {
+            error_type: RuntimeErrorType::UndefinedKey {
+                key: "\"b\"".into()
+            },
+            span: Span::new(0, 6, 1, 1),
+        })
+    );
+}
+
+#[test]
+fn test_vm_native_functions() {
+    let mut vm = Vm::default();
+    interpret_stmts(
+        r#"
+        var start = clock();
+        var elapsed = clock() - start;
+        var native = clock;
+    "#,
+        &mut vm,
+    );
+    assert!(matches!(vm.get_var("elapsed"), Value::Number(n) if n >= 0.0));
+    assert_eq!(vm.get_var("native").to_string(), "<native fn clock>");
+    assert!(run("clock(1);", &mut vm).is_err());
+}
+
+#[test]
+fn test_vm_read_line() {
+    let stdout = Output::default();
+    let mut vm = Vm::default()
+        .with_stdout(stdout.clone())
+        .with_stdin("first\nsecond\r\nlast".as_bytes());
+    interpret_stmts(
+        r#"
+        var line = readLine();
+        while (line != nil) {
+            print "> " + line;
+            line = readLine();
+        }
+    "#,
+        &mut vm,
+    );
+    let output = String::from_utf8(stdout.0.borrow().clone()).unwrap();
+    assert_eq!(output, "> first\n> second\n> last\n");
+}
@@ -0,0 +1,145 @@
+use std::{cell::RefCell, cmp::Ordering, fmt::Debug, rc::Rc};
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    bytecode::Closure,
+    error::ConversionError,
+    function::Function,
+    host::{HostObject, NativeMethod},
+    value::Value,
+};
+
+/// A method body, as created by whichever backend declared the class, or by
+/// the host for classes defined in Rust.
+#[derive(Debug)]
+pub enum Method {
+    Function(Rc<Function>),
+    Closure(Rc<Closure>),
+    Native(Rc<NativeMethod>),
+}
+
+impl Method {
+    pub fn arity(&self) -> usize {
+        match self {
+            Method::Function(function) => function.arity(),
+            Method::Closure(closure) => closure.arity(),
+            Method::Native(native) => native.arity,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Class {
+    pub name: String,
+    pub superclass: Option<Rc<Class>>,
+    pub methods: FxHashMap<String, Method>,
+}
+
+impl Class {
+    pub fn new(
+        name: String,
+        superclass: Option<Rc<Class>>,
+        methods: FxHashMap<String, Method>,
+    ) -> Self {
+        Self {
+            name,
+            superclass,
+            methods,
+        }
+    }
+
+    /// Looks up a method on this class, then along its superclass chain.
+    pub fn find_method(&self, name: &str) -> Option<&Method> {
+        self.methods.get(name).or_else(|| {
+            self.superclass
+                .as_ref()
+                .and_then(|superclass| superclass.find_method(name))
+        })
+    }
+
+    /// Calling a class takes the same arguments as its initializer.
+    pub fn arity(&self) -> usize {
+        self.find_method("init").map_or(0, |init| init.arity())
+    }
+}
+
+pub struct Instance {
+    pub class: Rc<Class>,
+    fields: RefCell<FxHashMap<String, Value>>,
+    /// Set by the constructor of a host class.
+    host: RefCell<Option<Box<dyn HostObject>>>,
+}
+
+impl Instance {
+    pub fn new(class: Rc<Class>) -> Self {
+        Self {
+            class,
+            fields: RefCell::default(),
+            host: RefCell::default(),
+        }
+    }
+
+    pub fn get_field(&self, name: &str) -> Option<Value> {
+        let field = self.fields.borrow().get(name).cloned();
+        field.or_else(|| self.host.borrow().as_ref()?.get_property(name))
+    }
+
+    /// Sets a field, converting the value when it is a property of the
+    /// host object.
+    pub fn set_field(&self, name: &str, value: Value) -> Result<(), ConversionError> {
+        if let Some(host) = self.host.borrow_mut().as_mut() {
+            if let Some(result) = host.set_property(name, &value) {
+                return result;
+            }
+        }
+        self.fields.borrow_mut().insert(name.into(), value);
+        Ok(())
+    }
+
+    pub fn set_host(&self, host: Box<dyn HostObject>) {
+        *self.host.borrow_mut() = Some(host);
+    }
+
+    /// Runs `f` on the host object if it is a `T`.
+    pub fn with_host<T: HostObject, R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let mut host = self.host.borrow_mut();
+        let host = host.as_mut()?.as_any_mut().downcast_mut::<T>()?;
+        Some(f(host))
+    }
+}
+
+// Fields may refer back to the instance, so they are left out to avoid
+// recursing forever.
+impl Debug for Instance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Instance")
+            .field("class", &self.class.name)
+            .finish_non_exhaustive()
+    }
+}
+
+// Classes and instances compare by identity.
+impl PartialEq for Class {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl PartialOrd for Class {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self == other).then_some(Ordering::Equal)
+    }
+}
+
+impl PartialEq for Instance {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl PartialOrd for Instance {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self == other).then_some(Ordering::Equal)
+    }
+}
@@ -0,0 +1,295 @@
+//! Conversions between Rust types and Lox [`Value`]s, so native functions
+//! can take and return plain Rust types.
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use crate::{
+    error::{ConversionError, RuntimeErrorType},
+    value::{MapKey, Value},
+};
+
+pub trait IntoLox {
+    fn into_lox(self) -> Value;
+}
+
+pub trait FromLox: Sized {
+    fn from_lox(value: &Value) -> Result<Self, ConversionError>;
+}
+
+/// The return value of a host method, which may be fallible.
+pub trait IntoLoxResult {
+    fn into_lox_result(self) -> Result<Value, RuntimeErrorType>;
+}
+
+impl<T: IntoLox> IntoLoxResult for T {
+    fn into_lox_result(self) -> Result<Value, RuntimeErrorType> {
+        Ok(self.into_lox())
+    }
+}
+
+impl<T: IntoLox, E: Into<RuntimeErrorType>> IntoLoxResult for Result<T, E> {
+    fn into_lox_result(self) -> Result<Value, RuntimeErrorType> {
+        self.map(T::into_lox).map_err(E::into)
+    }
+}
+
+/// Converts the arguments of a native function call. Implemented for tuples,
+/// whose length is the function's arity.
+pub trait FromArgs: Sized {
+    const ARITY: usize;
+
+    fn from_args(args: &[Value]) -> Result<Self, ConversionError>;
+}
+
+fn mismatch(expected: &'static str, actual: &Value) -> ConversionError {
+    ConversionError::TypeMismatch {
+        expected,
+        actual: actual.type_name(),
+    }
+}
+
+impl IntoLox for Value {
+    fn into_lox(self) -> Value {
+        self
+    }
+}
+
+impl FromLox for Value {
+    fn from_lox(value: &Value) -> Result<Self, ConversionError> {
+        Ok(value.clone())
+    }
+}
+
+impl IntoLox for () {
+    fn into_lox(self) -> Value {
+        Value::Nil
+    }
+}
+
+impl IntoLox for f64 {
+    fn into_lox(self) -> Value {
+        Value::Number(self)
+    }
+}
+
+impl FromLox for f64 {
+    fn from_lox(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::Number(n) => Ok(*n),
+            value => Err(mismatch("number", value)),
+        }
+    }
+}
+
+impl IntoLox for f32 {
+    fn into_lox(self) -> Value {
+        Value::Number(self.into())
+    }
+}
+
+impl FromLox for f32 {
+    fn from_lox(value: &Value) -> Result<Self, ConversionError> {
+        f64::from_lox(value).map(|n| n as f32)
+    }
+}
+
+macro_rules! impl_integer {
+    ($($int:ty),*) => {
+        $(
+            impl IntoLox for $int {
+                fn into_lox(self) -> Value {
+                    Value::Number(self as f64)
+                }
+            }
+
+            impl FromLox for $int {
+                fn from_lox(value: &Value) -> Result<Self, ConversionError> {
+                    let n = f64::from_lox(value)?;
+                    // `MAX` rounds up as an `f64` for 64-bit types, so bound
+                    // above by the next power of two, which is exact.
+                    let value_bits = <$int>::BITS as i32 - (<$int>::MIN != 0) as i32;
+                    let end = 2f64.powi(value_bits);
+                    if n.fract() == 0.0 && n >= <$int>::MIN as f64 && n < end {
+                        Ok(n as $int)
+                    } else {
+                        Err(ConversionError::OutOfRange {
+                            value: n,
+                            target: stringify!($int),
+                        })
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl IntoLox for bool {
+    fn into_lox(self) -> Value {
+        Value::Bool(self)
+    }
+}
+
+impl FromLox for bool {
+    fn from_lox(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::Bool(b) => Ok(*b),
+            value => Err(mismatch("bool", value)),
+        }
+    }
+}
+
+impl IntoLox for String {
+    fn into_lox(self) -> Value {
+        Value::String(self)
+    }
+}
+
+impl IntoLox for &str {
+    fn into_lox(self) -> Value {
+        Value::String(self.into())
+    }
+}
+
+impl FromLox for String {
+    fn from_lox(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::String(s) => Ok(s.clone()),
+            value => Err(mismatch("string", value)),
+        }
+    }
+}
+
+/// `None` is `nil`.
+impl<T: IntoLox> IntoLox for Option<T> {
+    fn into_lox(self) -> Value {
+        self.map_or(Value::Nil, T::into_lox)
+    }
+}
+
+impl<T: FromLox> FromLox for Option<T> {
+    fn from_lox(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::Nil => Ok(None),
+            value => T::from_lox(value).map(Some),
+        }
+    }
+}
+
+impl<T: IntoLox> IntoLox for Vec<T> {
+    fn into_lox(self) -> Value {
+        Value::list(self.into_iter().map(T::into_lox).collect())
+    }
+}
+
+/// Copies the elements out of a list.
+impl<T: FromLox> FromLox for Vec<T> {
+    fn from_lox(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::List(list) => list.borrow().iter().map(T::from_lox).collect(),
+            value => Err(mismatch("list", value)),
+        }
+    }
+}
+
+/// The list itself, for natives that modify it in place.
+impl IntoLox for Rc<RefCell<Vec<Value>>> {
+    fn into_lox(self) -> Value {
+        Value::List(self)
+    }
+}
+
+impl FromLox for Rc<RefCell<Vec<Value>>> {
+    fn from_lox(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::List(list) => Ok(Rc::clone(list)),
+            value => Err(mismatch("list", value)),
+        }
+    }
+}
+
+/// The map itself, for natives that modify it in place.
+impl IntoLox for Rc<RefCell<BTreeMap<MapKey, Value>>> {
+    fn into_lox(self) -> Value {
+        Value::Map(self)
+    }
+}
+
+impl FromLox for Rc<RefCell<BTreeMap<MapKey, Value>>> {
+    fn from_lox(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::Map(map) => Ok(Rc::clone(map)),
+            value => Err(mismatch("map", value)),
+        }
+    }
+}
+
+macro_rules! impl_from_args {
+    ($len:literal; $($arg:ident),*) => {
+        impl<$($arg: FromLox),*> FromArgs for ($($arg,)*) {
+            const ARITY: usize = $len;
+
+            #[allow(unused_variables, unused_mut)]
+            fn from_args(args: &[Value]) -> Result<Self, ConversionError> {
+                if args.len() != Self::ARITY {
+                    return Err(ConversionError::ArityMismatch {
+                        expected: Self::ARITY,
+                        found: args.len(),
+                    });
+                }
+                let mut args = args.iter();
+                Ok(($($arg::from_lox(args.next().unwrap())?,)*))
+            }
+        }
+    };
+}
+
+impl_from_args!(0;);
+impl_from_args!(1; A);
+impl_from_args!(2; A, B);
+impl_from_args!(3; A, B, C);
+impl_from_args!(4; A, B, C, D);
+impl_from_args!(5; A, B, C, D, E);
+impl_from_args!(6; A, B, C, D, E, F);
+
+/// Tuples other than `()`, which is `nil`, convert to and from lists of the
+/// same length.
+macro_rules! impl_tuple {
+    ($len:literal; $($arg:ident),*) => {
+        impl<$($arg: IntoLox),*> IntoLox for ($($arg,)*) {
+            #[allow(non_snake_case)]
+            fn into_lox(self) -> Value {
+                let ($($arg,)*) = self;
+                Value::list(vec![$($arg.into_lox()),*])
+            }
+        }
+
+        impl<$($arg: FromLox),*> FromLox for ($($arg,)*) {
+            fn from_lox(value: &Value) -> Result<Self, ConversionError> {
+                let Value::List(list) = value else {
+                    return Err(mismatch("list", value));
+                };
+                let list = list.borrow();
+                if list.len() != $len {
+                    return Err(ConversionError::LengthMismatch {
+                        expected: $len,
+                        found: list.len(),
+                    });
+                }
+                let mut elements = list.iter();
+                Ok(($($arg::from_lox(elements.next().unwrap())?,)*))
+            }
+        }
+    };
+}
+
+impl_tuple!(1; A);
+impl_tuple!(2; A, B);
+impl_tuple!(3; A, B, C);
+impl_tuple!(4; A, B, C, D);
+impl_tuple!(5; A, B, C, D, E);
+impl_tuple!(6; A, B, C, D, E, F);
+
+#[cfg(test)]
+mod tests;
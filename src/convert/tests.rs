@@ -0,0 +1,159 @@
+use super::*;
+use crate::{error::RuntimeErrorType, Interpreter};
+
+#[test]
+fn test_scalars_round_trip() {
+    assert_eq!(f64::from_lox(&1.5.into_lox()), Ok(1.5));
+    assert_eq!(i32::from_lox(&(-7).into_lox()), Ok(-7));
+    assert_eq!(usize::from_lox(&Value::Number(3.0)), Ok(3));
+    assert_eq!(bool::from_lox(&true.into_lox()), Ok(true));
+    assert_eq!(String::from_lox(&"lox".into_lox()), Ok("lox".into()));
+    assert_eq!(().into_lox(), Value::Nil);
+}
+
+#[test]
+fn test_vec() {
+    let list = vec![1, 2, 3].into_lox();
+    assert_eq!(list.to_string(), "[1, 2, 3]");
+    assert_eq!(Vec::<u8>::from_lox(&list), Ok(vec![1, 2, 3]));
+    assert_eq!(
+        Vec::<bool>::from_lox(&list),
+        Err(ConversionError::TypeMismatch {
+            expected: "bool",
+            actual: "number"
+        })
+    );
+
+    // The shared handle sees changes made through the value.
+    let shared = Rc::<RefCell<Vec<Value>>>::from_lox(&list).unwrap();
+    list.set_index(&Value::Number(0.0), Value::Nil).unwrap();
+    assert_eq!(shared.borrow()[0], Value::Nil);
+}
+
+#[test]
+fn test_tuple() {
+    let pair = (1, "a").into_lox();
+    assert_eq!(pair.to_string(), "[1, \"a\"]");
+    assert_eq!(<(u8, String)>::from_lox(&pair), Ok((1, "a".into())));
+    assert_eq!(
+        <(u8, String, bool)>::from_lox(&pair),
+        Err(ConversionError::LengthMismatch {
+            expected: 3,
+            found: 2
+        })
+    );
+    assert_eq!(
+        <(String, String)>::from_lox(&pair),
+        Err(ConversionError::TypeMismatch {
+            expected: "string",
+            actual: "number"
+        })
+    );
+    assert_eq!(
+        <(f64,)>::from_lox(&Value::Nil),
+        Err(ConversionError::TypeMismatch {
+            expected: "list",
+            actual: "nil"
+        })
+    );
+
+    let sextuple = (1, 2, 3, 4, 5, (true,)).into_lox();
+    assert_eq!(sextuple.to_string(), "[1, 2, 3, 4, 5, [true]]");
+    assert_eq!(
+        <(u8, u8, u8, u8, u8, (bool,))>::from_lox(&sextuple),
+        Ok((1, 2, 3, 4, 5, (true,)))
+    );
+}
+
+#[test]
+fn test_option() {
+    assert_eq!(None::<f64>.into_lox(), Value::Nil);
+    assert_eq!(Some(2.0).into_lox(), Value::Number(2.0));
+    assert_eq!(Option::<f64>::from_lox(&Value::Nil), Ok(None));
+    assert_eq!(Option::<f64>::from_lox(&Value::Number(2.0)), Ok(Some(2.0)));
+}
+
+#[test]
+fn test_conversion_errors() {
+    assert_eq!(
+        f64::from_lox(&Value::Bool(true)),
+        Err(ConversionError::TypeMismatch {
+            expected: "number",
+            actual: "bool"
+        })
+    );
+    assert_eq!(
+        u8::from_lox(&Value::Number(256.0)),
+        Err(ConversionError::OutOfRange {
+            value: 256.0,
+            target: "u8"
+        })
+    );
+    assert_eq!(
+        i64::from_lox(&Value::Number(1.5)),
+        Err(ConversionError::OutOfRange {
+            value: 1.5,
+            target: "i64"
+        })
+    );
+    assert_eq!(
+        i64::from_lox(&Value::Number(2f64.powi(63))),
+        Err(ConversionError::OutOfRange {
+            value: 2f64.powi(63),
+            target: "i64"
+        })
+    );
+    assert_eq!(
+        u64::from_lox(&Value::Number(2f64.powi(64))),
+        Err(ConversionError::OutOfRange {
+            value: 2f64.powi(64),
+            target: "u64"
+        })
+    );
+    assert_eq!(i64::from_lox(&Value::Number(-2f64.powi(63))), Ok(i64::MIN));
+    assert_eq!(u8::from_lox(&Value::Number(255.0)), Ok(u8::MAX));
+    assert_eq!(
+        <(f64, String)>::from_args(&[Value::Number(1.0)]),
+        Err(ConversionError::ArityMismatch {
+            expected: 2,
+            found: 1
+        })
+    );
+}
+
+#[test]
+fn test_from_args() {
+    let args = [Value::Number(1.0), Value::String("a".into()), Value::Nil];
+    assert_eq!(
+        <(u32, String, Option<bool>)>::from_args(&args),
+        Ok((1, "a".into(), None))
+    );
+    assert_eq!(<()>::ARITY, 0);
+}
+
+#[test]
+fn test_define_fn() {
+    let mut int = Interpreter::default();
+    int.define_fn("repeat", |(s, n): (String, usize)| Ok(s.repeat(n)));
+    int.define_fn("half", |(n,): (i64,)| match n % 2 {
+        0 => Ok(n / 2),
+        _ => Err(RuntimeErrorType::Native {
+            message: format!("{} is odd", n),
+        }),
+    });
+    assert_eq!(
+        int.eval_str("repeat(\"ab\", half(4));"),
+        Ok(Value::String("abab".into()))
+    );
+
+    let error = |result: Result<Value, Vec<crate::LoxError>>| result.unwrap_err()[0].to_string();
+    assert_eq!(error(int.eval_str("half(3);")), "line 1:1: 3 is odd");
+    assert_eq!(
+        error(int.eval_str("repeat(1, 2);")),
+        "line 1:1: Expected string, found number"
+    );
+    assert_eq!(
+        error(int.eval_str("half(0.5);")),
+        "line 1:1: Cannot convert 0.5 to `i64`"
+    );
+}
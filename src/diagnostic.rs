@@ -0,0 +1,211 @@
+use std::fmt::Write;
+
+use colored::{ColoredString, Colorize};
+
+use crate::{
+    error::{
+        CompileError, LoxError, ParseError, ResolveError, ResolveErrorType, RuntimeError,
+        RuntimeErrorType, SyntaxError,
+    },
+    span::Span,
+};
+
+/// An error report that can be rendered against the source it came from,
+/// with the offending code underlined.
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub message: String,
+    /// Where the error happened. Errors without a location render as just
+    /// their message.
+    pub span: Option<Span>,
+    /// Secondary locations related to the error.
+    pub labels: Vec<Label>,
+    pub help: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl ToString, span: Span) -> Self {
+        Self {
+            message: message.to_string(),
+            span: Some(span),
+            labels: Vec::new(),
+            help: None,
+        }
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl ToString) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.to_string(),
+        });
+        self
+    }
+
+    pub fn with_help(mut self, help: impl ToString) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    /// Renders the diagnostic with snippets of `source`, using terminal
+    /// colors only when `color` is set.
+    pub fn render(&self, source: &str, color: bool) -> String {
+        let paint = |text: &str, style: fn(&str) -> ColoredString| {
+            if color {
+                style(text).to_string()
+            } else {
+                text.to_string()
+            }
+        };
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            "{}: {}",
+            paint("error", |s| s.red().bold()),
+            paint(&self.message, |s| s.bold())
+        )
+        .unwrap();
+        let Some(span) = self.span else {
+            return out;
+        };
+
+        let mut marks = vec![(span, '^', "")];
+        marks.extend(
+            self.labels
+                .iter()
+                .map(|l| (l.span, '-', l.message.as_str())),
+        );
+        marks.sort_by_key(|(span, _, _)| span.line);
+        let marks = marks
+            .into_iter()
+            .filter_map(|(span, marker, message)| {
+                let line_text = source.lines().nth(span.line.checked_sub(1)? as usize)?;
+                Some((span, line_text, marker, message))
+            })
+            .collect::<Vec<_>>();
+
+        let width = marks.iter().map(|m| m.0.line).max().unwrap_or(span.line);
+        let width = width.to_string().len();
+        let gutter = paint(&format!("{:width$} |", ""), |s| s.blue().bold());
+        writeln!(
+            out,
+            "{:width$}{} {}",
+            "",
+            paint("-->", |s| s.blue().bold()),
+            span
+        )
+        .unwrap();
+        if marks.is_empty() {
+            return out;
+        }
+
+        writeln!(out, "{}", gutter).unwrap();
+        let mut previous_line = None;
+        for (span, line_text, marker, message) in marks {
+            if previous_line != Some(span.line) {
+                let number = paint(&format!("{:>width$} |", span.line), |s| s.blue().bold());
+                writeln!(out, "{} {}", number, line_text).unwrap();
+                previous_line = Some(span.line);
+            }
+
+            let indent = " ".repeat(span.column.saturating_sub(1) as usize);
+            let underline = marker.to_string().repeat(underline_len(source, span));
+            let mark = match marker {
+                '^' => paint(&underline, |s| s.red().bold()),
+                _ => paint(&underline, |s| s.blue().bold()),
+            };
+            let mark = format!("{}{} {}", indent, mark, message);
+            writeln!(out, "{} {}", gutter, mark.trim_end()).unwrap();
+        }
+
+        if let Some(help) = &self.help {
+            writeln!(
+                out,
+                "{:width$} = {}: {}",
+                "",
+                paint("help", |s| s.bold()),
+                help
+            )
+            .unwrap();
+        }
+        out
+    }
+}
+
+/// Number of characters to underline for `span`, stopping at the end of its
+/// first line.
+fn underline_len(source: &str, span: Span) -> usize {
+    let len = source
+        .get(span.start..span.end)
+        .map_or(1, |text| text.chars().take_while(|&c| c != '\n').count());
+    len.max(1)
+}
+
+impl From<&SyntaxError> for Diagnostic {
+    fn from(error: &SyntaxError) -> Self {
+        Diagnostic::new(&error.error_type, error.span)
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Self {
+        Diagnostic::new(&error.error_type, error.span)
+    }
+}
+
+impl From<&ResolveError> for Diagnostic {
+    fn from(error: &ResolveError) -> Self {
+        let diagnostic = Diagnostic::new(&error.error_type, error.span);
+        match &error.error_type {
+            ResolveErrorType::AlreadyDeclared { name, previous } => {
+                diagnostic.with_label(*previous, format!("`{}` first declared here", name))
+            }
+            ResolveErrorType::ReturnFromInitializer => {
+                diagnostic.with_help("initializers always return `this`")
+            }
+            ResolveErrorType::SuperWithoutSuperclass => {
+                diagnostic.with_help("declare a superclass with `class Name < Superclass`")
+            }
+            _ => diagnostic,
+        }
+    }
+}
+
+impl From<&CompileError> for Diagnostic {
+    fn from(error: &CompileError) -> Self {
+        Diagnostic::new(&error.error_type, error.span)
+    }
+}
+
+impl From<&RuntimeError> for Diagnostic {
+    fn from(error: &RuntimeError) -> Self {
+        let diagnostic = Diagnostic::new(&error.error_type, error.span);
+        match &error.error_type {
+            RuntimeErrorType::NotAnInstance => {
+                diagnostic.with_help("only class instances have fields and methods")
+            }
+            _ => diagnostic,
+        }
+    }
+}
+
+impl From<&LoxError> for Diagnostic {
+    fn from(error: &LoxError) -> Self {
+        match error {
+            LoxError::SyntaxError(error) => error.into(),
+            LoxError::ParseError(error) => error.into(),
+            LoxError::ResolveError(error) => error.into(),
+            LoxError::CompileError(error) => error.into(),
+            LoxError::RuntimeError(error) => error.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
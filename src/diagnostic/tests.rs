@@ -0,0 +1,65 @@
+use insta::{assert_snapshot, with_settings};
+
+use super::*;
+use crate::{parser::Parser, resolver::Resolver, scanner::Scanner};
+
+/// Runs the front end over `source` and renders the first error it reports.
+fn render_error(source: &'static str) -> String {
+    let tokens = match Scanner::new(source.to_string()).scan_source() {
+        Ok(tokens) => tokens,
+        Err(errors) => return Diagnostic::from(&errors[0]).render(source, false),
+    };
+    let errors = match Parser::new(tokens).parse() {
+        Ok(stmts) => Resolver::default().resolve(&stmts).unwrap_err(),
+        Err(errors) => errors,
+    };
+    Diagnostic::from(&errors[0]).render(source, false)
+}
+
+macro_rules! snapshot_test {
+    ($name:ident, $( $source:literal ),*) => {
+        #[test]
+        fn $name() {
+            $(
+                let source: &'static str = $source;
+                with_settings!({ description => source }, {
+                    assert_snapshot!(render_error(source));
+                });
+            )*
+        }
+    };
+}
+
+snapshot_test!(test_render_syntax_error, "var a = 1;\nvar b = a # 2;");
+snapshot_test!(test_render_parse_error, "print (1 + 2;");
+snapshot_test!(
+    test_render_secondary_label,
+    "{\n  var count = 1;\n  var count = 2;\n}"
+);
+snapshot_test!(
+    test_render_help,
+    "class A {\n  init() {\n    return 1;\n  }\n}"
+);
+
+#[test]
+fn test_render_without_span() {
+    let diagnostic = Diagnostic {
+        message: "Something went wrong".into(),
+        span: None,
+        labels: Vec::new(),
+        help: None,
+    };
+    assert_eq!(
+        diagnostic.render("", false),
+        "error: Something went wrong\n"
+    );
+}
+
+#[test]
+fn test_render_color() {
+    colored::control::set_override(true);
+    let diagnostic = Diagnostic::new("Oops", Span::new(0, 3, 1, 1));
+    let rendered = diagnostic.render("bad", true);
+    assert!(rendered.contains("\x1b["));
+    assert!(!diagnostic.render("bad", false).contains("\x1b["));
+}
@@ -1,65 +1,101 @@
-use anyhow::{bail, Result};
+use std::{cell::RefCell, rc::Rc};
+
 use rustc_hash::FxHashMap;
 
-use crate::value::Value;
+use crate::{
+    error::{Result, RuntimeError, RuntimeErrorType},
+    span::Span,
+    value::Value,
+};
 
 #[derive(Debug, Default)]
 struct Scope {
     values: FxHashMap<String, Value>,
+    enclosing: Option<Environment>,
 }
 
-#[derive(Debug)]
+/// A handle to a scope and the chain of scopes enclosing it.
+///
+/// Clones share the same underlying scope, which lets closures keep the scope
+/// they were defined in alive after the interpreter has left it.
+#[derive(Debug, Clone, Default)]
 pub struct Environment {
-    scopes: Vec<Scope>,
+    scope: Rc<RefCell<Scope>>,
 }
 
-impl Default for Environment {
-    fn default() -> Self {
-        let scopes = vec![Scope::default()];
-        Self { scopes }
+impl Environment {
+    pub fn new_enclosed(enclosing: &Environment) -> Self {
+        let scope = Scope {
+            values: FxHashMap::default(),
+            enclosing: Some(enclosing.clone()),
+        };
+        Self {
+            scope: Rc::new(RefCell::new(scope)),
+        }
     }
-}
 
-impl Environment {
-    pub fn define(&mut self, name: &str, value: Value) {
-        self.scopes
-            .last_mut()
-            .unwrap()
-            .values
-            .insert(name.into(), value);
+    pub fn define(&self, name: &str, value: Value) {
+        self.scope.borrow_mut().values.insert(name.into(), value);
     }
 
-    pub fn assign(&mut self, name: &str, value: Value) -> Result<Value> {
-        let frames = self.scopes.iter_mut().rev();
-        for frame in frames {
-            if !frame.values.contains_key(name) {
-                continue;
+    /// Assigns to the nearest variable called `name`, reporting an error at
+    /// `span` if there is none.
+    pub fn assign(&self, name: &str, value: Value, span: Span) -> Result<Value> {
+        let mut scope = self.scope.borrow_mut();
+        if let Some(slot) = scope.values.get_mut(name) {
+            *slot = value.clone();
+            return Ok(value);
+        }
+        match &scope.enclosing {
+            Some(enclosing) => enclosing.assign(name, value, span),
+            None => Err(RuntimeError {
+                error_type: RuntimeErrorType::UndefinedVariable { name: name.into() },
+                span,
             }
-
-            return frame.values.insert(name.into(), value).map(Ok).unwrap();
+            .into()),
         }
-        bail!("{} is undefined", name);
     }
 
-    pub fn get(&self, name: &str) -> Option<&Value> {
-        let frames = self.scopes.iter().rev();
-        for frame in frames {
-            if !frame.values.contains_key(name) {
-                continue;
-            }
-            return frame.values.get(name);
+    /// Assigns to a variable declared `distance` scopes up the chain.
+    pub fn assign_at(&self, distance: usize, name: &str, value: Value) -> Value {
+        self.ancestor(distance).define(name, value.clone());
+        value
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        let scope = self.scope.borrow();
+        match scope.values.get(name) {
+            Some(value) => Some(value.clone()),
+            None => scope.enclosing.as_ref().and_then(|e| e.get(name)),
         }
-        None
     }
 
-    pub fn push_scope(&mut self) {
-        self.scopes.push(Scope::default());
+    /// The variables declared directly in this scope.
+    pub fn values(&self) -> Vec<(String, Value)> {
+        let scope = self.scope.borrow();
+        scope
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Gets a variable declared `distance` scopes up the chain.
+    pub fn get_at(&self, distance: usize, name: &str) -> Option<Value> {
+        self.ancestor(distance)
+            .scope
+            .borrow()
+            .values
+            .get(name)
+            .cloned()
     }
 
-    pub fn pop_scope(&mut self) {
-        if self.scopes.len() == 1 {
-            panic!("Attempted to pop global scope");
+    fn ancestor(&self, distance: usize) -> Environment {
+        let mut env = self.clone();
+        for _ in 0..distance {
+            let enclosing = env.scope.borrow().enclosing.clone();
+            env = enclosing.expect("Resolved scope depth should be in the chain");
         }
-        self.scopes.pop();
+        env
     }
 }
@@ -1,59 +1,439 @@
-use std::{error::Error, fmt::Display};
+use std::{error::Error, fmt::Display, io};
 
-use crate::token::TokenType;
+use crate::{impl_from_inner, span::Span, token::TokenType};
 
+pub type Result<T, E = LoxError> = std::result::Result<T, E>;
+
+/// Any error reported while scanning, parsing, resolving, compiling or
+/// running Lox code.
+#[derive(Debug, PartialEq)]
+#[allow(clippy::enum_variant_names)]
+pub enum LoxError {
+    SyntaxError(SyntaxError),
+    ParseError(ParseError),
+    ResolveError(ResolveError),
+    CompileError(CompileError),
+    RuntimeError(RuntimeError),
+}
+
+impl_from_inner!(SyntaxError, LoxError);
+impl_from_inner!(ParseError, LoxError);
+impl_from_inner!(ResolveError, LoxError);
+impl_from_inner!(CompileError, LoxError);
+impl_from_inner!(RuntimeError, LoxError);
+
+impl LoxError {
+    /// Whether the error comes from the source ending early, so that more
+    /// input could fix it.
+    pub fn is_incomplete(&self) -> bool {
+        match self {
+            LoxError::SyntaxError(e) => e.error_type == SyntaxErrorType::UnterminatedString,
+            LoxError::ParseError(e) => matches!(
+                e.error_type,
+                ParseErrorType::UnexpectedToken {
+                    found: TokenType::EOF,
+                    ..
+                } | ParseErrorType::ExpectedIdentifier {
+                    found: TokenType::EOF
+                } | ParseErrorType::ExpectedExpression {
+                    found: TokenType::EOF
+                }
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl Display for LoxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoxError::SyntaxError(e) => e.fmt(f),
+            LoxError::ParseError(e) => e.fmt(f),
+            LoxError::ResolveError(e) => e.fmt(f),
+            LoxError::CompileError(e) => e.fmt(f),
+            LoxError::RuntimeError(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for LoxError {}
 impl Error for SyntaxError {}
 impl Error for ParseError {}
+impl Error for ResolveError {}
+impl Error for CompileError {}
 impl Error for RuntimeError {}
 
 #[derive(Debug, PartialEq)]
 pub struct SyntaxError {
-    pub line_num: u32,
+    pub error_type: SyntaxErrorType,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SyntaxErrorType {
+    UnexpectedCharacter { found: char },
+    InvalidNumber,
+    UnterminatedString,
+    InvalidEscape { sequence: String },
+}
+
+impl Display for SyntaxErrorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyntaxErrorType::UnexpectedCharacter { found } => {
+                write!(f, "Unexpected character `{}`", found)
+            }
+            SyntaxErrorType::InvalidNumber => write!(f, "Invalid number literal"),
+            SyntaxErrorType::UnterminatedString => write!(f, "Unterminated string"),
+            SyntaxErrorType::InvalidEscape { sequence } => {
+                write!(f, "Invalid escape sequence `{}`", sequence)
+            }
+        }
+    }
 }
 
 impl Display for SyntaxError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "syntax error on line {}", self.line_num)
+        write!(f, "line {}: {}", self.span, self.error_type)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct ParseError {
     pub error_type: ParseErrorType,
-    pub line_num: u32,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum ParseErrorType {
     UnexpectedToken {
         expected: TokenType,
         found: TokenType,
     },
+    ExpectedIdentifier {
+        found: TokenType,
+    },
+    ExpectedExpression {
+        found: TokenType,
+    },
+    InvalidAssignmentTarget,
+    UndefinedLabel {
+        name: String,
+    },
+    ShadowedLabel {
+        name: String,
+    },
 }
 
-impl Display for ParseError {
+impl Display for ParseErrorType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self.error_type {
+        match self {
             ParseErrorType::UnexpectedToken { expected, found } => {
                 write!(
                     f,
-                    "line {}: Expected `{}`, found `{}`",
-                    self.line_num,
+                    "Expected `{}`, found `{}`",
                     expected.lexeme(),
                     found.lexeme()
                 )
             }
+            ParseErrorType::ExpectedIdentifier { found } => {
+                write!(f, "Expected identifier, found `{}`", found.lexeme())
+            }
+            ParseErrorType::ExpectedExpression { found } => {
+                write!(f, "Expected expression, found `{}`", found.lexeme())
+            }
+            ParseErrorType::InvalidAssignmentTarget => write!(f, "Invalid assignment target"),
+            ParseErrorType::UndefinedLabel { name } => {
+                write!(f, "No enclosing loop is labeled `{}`", name)
+            }
+            ParseErrorType::ShadowedLabel { name } => {
+                write!(f, "Label `{}` is already used by an enclosing loop", name)
+            }
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.span, self.error_type)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ResolveError {
+    pub error_type: ResolveErrorType,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ResolveErrorType {
+    ReadInOwnInitializer { name: String },
+    AlreadyDeclared { name: String, previous: Span },
+    TopLevelReturn,
+    ReturnFromInitializer,
+    ThisOutsideClass,
+    SuperOutsideClass,
+    SuperWithoutSuperclass,
+    InheritFromSelf { name: String },
+    BreakOutsideLoop,
+    ContinueOutsideLoop,
+}
+
+impl Display for ResolveErrorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResolveErrorType::ReadInOwnInitializer { name } => {
+                write!(
+                    f,
+                    "Cannot read local variable `{}` in its own initializer",
+                    name
+                )
+            }
+            ResolveErrorType::AlreadyDeclared { name, .. } => {
+                write!(f, "`{}` is already declared in this scope", name)
+            }
+            ResolveErrorType::TopLevelReturn => write!(f, "Cannot return from top-level code"),
+            ResolveErrorType::ReturnFromInitializer => {
+                write!(f, "Cannot return a value from an initializer")
+            }
+            ResolveErrorType::ThisOutsideClass => {
+                write!(f, "Cannot use `this` outside of a class")
+            }
+            ResolveErrorType::SuperOutsideClass => {
+                write!(f, "Cannot use `super` outside of a class")
+            }
+            ResolveErrorType::SuperWithoutSuperclass => {
+                write!(f, "Cannot use `super` in a class with no superclass")
+            }
+            ResolveErrorType::InheritFromSelf { name } => {
+                write!(f, "Class `{}` cannot inherit from itself", name)
+            }
+            ResolveErrorType::BreakOutsideLoop => write!(f, "Cannot use `break` outside of a loop"),
+            ResolveErrorType::ContinueOutsideLoop => {
+                write!(f, "Cannot use `continue` outside of a loop")
+            }
+        }
+    }
+}
+
+impl Display for ResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.span, self.error_type)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CompileError {
+    pub error_type: CompileErrorType,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CompileErrorType {
+    TooManyConstants,
+    TooManyLocals,
+    TooManyUpvalues,
+    TooManyArguments,
+    TooManyMethods,
+    TooManyElements,
+    JumpTooLarge,
+    InvalidAssignmentTarget,
+}
+
+impl Display for CompileErrorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompileErrorType::TooManyConstants => write!(f, "Too many constants in one chunk"),
+            CompileErrorType::TooManyLocals => write!(f, "Too many local variables in function"),
+            CompileErrorType::TooManyUpvalues => {
+                write!(f, "Too many closure variables in function")
+            }
+            CompileErrorType::TooManyArguments => {
+                write!(f, "Cannot have more than 255 arguments")
+            }
+            CompileErrorType::TooManyMethods => write!(f, "Cannot have more than 255 methods"),
+            CompileErrorType::TooManyElements => {
+                write!(f, "Cannot have more than 255 elements in a literal")
+            }
+            CompileErrorType::JumpTooLarge => write!(f, "Too much code to jump over"),
+            CompileErrorType::InvalidAssignmentTarget => write!(f, "Invalid assignment target"),
         }
     }
 }
 
-#[derive(Debug)]
+impl Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.span, self.error_type)
+    }
+}
+
+#[derive(Debug, PartialEq)]
 pub struct RuntimeError {
-    pub line_num: u32,
+    pub error_type: RuntimeErrorType,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RuntimeErrorType {
+    UndefinedVariable {
+        name: String,
+    },
+    TypeMismatch {
+        expected: &'static str,
+        actual: &'static str,
+    },
+    NotCallable,
+    ArityMismatch {
+        expected: usize,
+        found: usize,
+    },
+    NotAnInstance,
+    UndefinedProperty {
+        name: String,
+    },
+    SuperclassNotClass {
+        name: String,
+    },
+    StackOverflow,
+    NegativeIndex {
+        index: f64,
+    },
+    FractionalIndex {
+        index: f64,
+    },
+    IndexOutOfRange {
+        index: usize,
+        len: usize,
+    },
+    InvalidKey {
+        actual: &'static str,
+    },
+    UndefinedKey {
+        key: String,
+    },
+    /// Reported by a native function.
+    Native {
+        message: String,
+    },
+    Io {
+        message: String,
+    },
+}
+
+impl Display for RuntimeErrorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeErrorType::UndefinedVariable { name } => {
+                write!(f, "Undefined variable `{}`", name)
+            }
+            RuntimeErrorType::TypeMismatch { expected, actual } => {
+                write!(f, "Expected {}, found {}", expected, actual)
+            }
+            RuntimeErrorType::NotCallable => write!(f, "Can only call functions and classes"),
+            RuntimeErrorType::ArityMismatch { expected, found } => {
+                write!(f, "Expected {} arguments, found {}", expected, found)
+            }
+            RuntimeErrorType::NotAnInstance => write!(f, "Only instances have properties"),
+            RuntimeErrorType::UndefinedProperty { name } => {
+                write!(f, "Undefined property `{}`", name)
+            }
+            RuntimeErrorType::SuperclassNotClass { name } => {
+                write!(f, "Superclass `{}` must be a class", name)
+            }
+            RuntimeErrorType::StackOverflow => write!(f, "Stack overflow"),
+            RuntimeErrorType::NegativeIndex { index } => {
+                write!(f, "Index {} is negative", index)
+            }
+            RuntimeErrorType::FractionalIndex { index } => {
+                write!(f, "Index {} is not a whole number", index)
+            }
+            RuntimeErrorType::IndexOutOfRange { index, len } => {
+                write!(f, "Index {} is out of range for length {}", index, len)
+            }
+            RuntimeErrorType::InvalidKey { actual } => {
+                write!(f, "Cannot use {} as a map key", actual)
+            }
+            RuntimeErrorType::UndefinedKey { key } => write!(f, "Undefined key {}", key),
+            RuntimeErrorType::Native { message } => write!(f, "{}", message),
+            RuntimeErrorType::Io { message } => write!(f, "I/O error: {}", message),
+        }
+    }
 }
 
 impl Display for RuntimeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Runtime error: {}", self.line_num)
+        write!(f, "line {}: {}", self.span, self.error_type)
+    }
+}
+
+impl From<io::Error> for RuntimeErrorType {
+    fn from(error: io::Error) -> Self {
+        RuntimeErrorType::Io {
+            message: error.to_string(),
+        }
+    }
+}
+
+/// A Lox value that does not fit the Rust type a native function expects.
+#[derive(Debug, PartialEq)]
+pub enum ConversionError {
+    TypeMismatch {
+        expected: &'static str,
+        actual: &'static str,
+    },
+    OutOfRange {
+        value: f64,
+        target: &'static str,
+    },
+    ArityMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// A list converted to a tuple of a different length.
+    LengthMismatch {
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConversionError::TypeMismatch { expected, actual } => {
+                write!(f, "Expected {}, found {}", expected, actual)
+            }
+            ConversionError::OutOfRange { value, target } => {
+                write!(f, "Cannot convert {} to `{}`", value, target)
+            }
+            ConversionError::ArityMismatch { expected, found } => {
+                write!(f, "Expected {} arguments, found {}", expected, found)
+            }
+            ConversionError::LengthMismatch { expected, found } => {
+                write!(
+                    f,
+                    "Expected a list of {} elements, found {}",
+                    expected, found
+                )
+            }
+        }
+    }
+}
+
+impl Error for ConversionError {}
+
+impl From<ConversionError> for RuntimeErrorType {
+    fn from(error: ConversionError) -> Self {
+        match error {
+            ConversionError::TypeMismatch { expected, actual } => {
+                RuntimeErrorType::TypeMismatch { expected, actual }
+            }
+            ConversionError::ArityMismatch { expected, found } => {
+                RuntimeErrorType::ArityMismatch { expected, found }
+            }
+            error @ (ConversionError::OutOfRange { .. }
+            | ConversionError::LengthMismatch { .. }) => RuntimeErrorType::Native {
+                message: error.to_string(),
+            },
+        }
     }
 }
@@ -1,8 +1,11 @@
+use std::cell::Cell;
+
 use rlox_macros::Visitor;
 
 use crate::{
     impl_from_inner,
     operator::{BinaryOperator, UnaryOperator},
+    span::Span,
 };
 
 #[derive(Debug, Visitor)]
@@ -14,6 +17,15 @@ pub enum Expression {
     Grouping(Grouping),
     Var(Var),
     Assign(Assign),
+    List(List),
+    Map(Map),
+    Interpolation(Interpolation),
+    Index(Index),
+    Call(Call),
+    Get(Get),
+    Set(Set),
+    This(This),
+    Super(Super),
 }
 
 impl_from_inner!(Literal, Expression);
@@ -22,10 +34,48 @@ impl_from_inner!(Binary, Expression);
 impl_from_inner!(Grouping, Expression);
 impl_from_inner!(Var, Expression);
 impl_from_inner!(Assign, Expression);
+impl_from_inner!(List, Expression);
+impl_from_inner!(Map, Expression);
+impl_from_inner!(Interpolation, Expression);
+impl_from_inner!(Index, Expression);
+impl_from_inner!(Call, Expression);
+impl_from_inner!(Get, Expression);
+impl_from_inner!(Set, Expression);
+impl_from_inner!(This, Expression);
+impl_from_inner!(Super, Expression);
+
+impl Expression {
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::Literal(inner) => inner.span,
+            Expression::Unary(inner) => inner.span,
+            Expression::Binary(inner) => inner.span,
+            Expression::Grouping(inner) => inner.span,
+            Expression::Var(inner) => inner.span,
+            Expression::Assign(inner) => inner.span,
+            Expression::List(inner) => inner.span,
+            Expression::Map(inner) => inner.span,
+            Expression::Interpolation(inner) => inner.span,
+            Expression::Index(inner) => inner.span,
+            Expression::Call(inner) => inner.span,
+            Expression::Get(inner) => inner.span,
+            Expression::Set(inner) => inner.span,
+            Expression::This(inner) => inner.span,
+            Expression::Super(inner) => inner.span,
+        }
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct Literal {
+    pub value: LiteralValue,
+    pub span: Span,
+}
 
 #[derive(Debug)]
 #[cfg_attr(test, derive(serde::Serialize))]
-pub enum Literal {
+pub enum LiteralValue {
     Number(f64),
     String(String),
     True,
@@ -38,6 +88,7 @@ pub enum Literal {
 pub struct Unary {
     pub operator: UnaryOperator,
     pub expr: Box<Expression>,
+    pub span: Span,
 }
 
 #[derive(Debug)]
@@ -46,23 +97,115 @@ pub struct Binary {
     pub operator: BinaryOperator,
     pub left: Box<Expression>,
     pub right: Box<Expression>,
+    pub span: Span,
 }
 
 #[derive(Debug)]
 #[cfg_attr(test, derive(serde::Serialize))]
 pub struct Grouping {
     pub expr: Box<Expression>,
+    pub span: Span,
 }
 
 #[derive(Debug)]
 #[cfg_attr(test, derive(serde::Serialize))]
 pub struct Var {
     pub name: String,
+    pub span: Span,
+    /// Number of scopes between this use and the variable's declaration, as
+    /// recorded by the resolver. `None` refers to a global.
+    #[cfg_attr(test, serde(skip))]
+    pub depth: Cell<Option<usize>>,
+}
+
+impl Var {
+    pub fn new(name: String, span: Span) -> Self {
+        Self {
+            name,
+            span,
+            depth: Cell::default(),
+        }
+    }
 }
 
 #[derive(Debug)]
 #[cfg_attr(test, derive(serde::Serialize))]
 pub struct Assign {
-    pub name: Box<Expression>,
+    /// A `Var` or an `Index`.
+    pub target: Box<Expression>,
+    pub value: Box<Expression>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct List {
+    pub elements: Vec<Expression>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct Map {
+    pub entries: Vec<(Expression, Expression)>,
+    pub span: Span,
+}
+
+/// A string with `${...}` in it, as its literal segments and interpolated
+/// expressions in order.
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct Interpolation {
+    pub parts: Vec<Expression>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct Index {
+    pub object: Box<Expression>,
+    pub index: Box<Expression>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct Call {
+    pub callee: Box<Expression>,
+    pub arguments: Vec<Expression>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct Get {
+    pub object: Box<Expression>,
+    pub name: String,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct Set {
+    pub object: Box<Expression>,
+    pub name: String,
     pub value: Box<Expression>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct This {
+    pub span: Span,
+    #[cfg_attr(test, serde(skip))]
+    pub depth: Cell<Option<usize>>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct Super {
+    pub method: String,
+    pub span: Span,
+    #[cfg_attr(test, serde(skip))]
+    pub depth: Cell<Option<usize>>,
 }
@@ -0,0 +1,59 @@
+use std::{cmp::Ordering, fmt::Debug, rc::Rc};
+
+use crate::{class::Instance, environment::Environment, statement::FunDecl, value::Value};
+
+pub struct Function {
+    pub declaration: Rc<FunDecl>,
+    /// The environment the function was declared in.
+    pub closure: Environment,
+    /// Initializers always return `this`.
+    pub is_initializer: bool,
+}
+
+impl Function {
+    pub fn new(declaration: Rc<FunDecl>, closure: Environment, is_initializer: bool) -> Self {
+        Self {
+            declaration,
+            closure,
+            is_initializer,
+        }
+    }
+
+    /// Creates a copy of this method with `this` bound to `instance`.
+    pub fn bind(&self, instance: Rc<Instance>) -> Self {
+        let env = Environment::new_enclosed(&self.closure);
+        env.define("this", Value::Instance(instance));
+        Self::new(Rc::clone(&self.declaration), env, self.is_initializer)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.declaration.name
+    }
+
+    pub fn arity(&self) -> usize {
+        self.declaration.params.len()
+    }
+}
+
+// The closure usually contains the function itself, so it is left out to
+// avoid recursing forever.
+impl Debug for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Function")
+            .field("name", &self.name())
+            .finish_non_exhaustive()
+    }
+}
+
+// Functions compare by identity.
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl PartialOrd for Function {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self == other).then_some(Ordering::Equal)
+    }
+}
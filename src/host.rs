@@ -0,0 +1,86 @@
+//! Rust types exposed to Lox as classes, usually through
+//! `#[derive(LoxClass)]` and `#[lox_methods]`.
+
+use std::{any::Any, fmt::Debug, rc::Rc};
+
+use crate::{
+    class::Instance,
+    error::{ConversionError, RuntimeErrorType},
+    native::NativeFunction,
+    value::Value,
+};
+
+/// The Rust value backing an instance of a host class. Its fields show up as
+/// properties of the instance.
+pub trait HostObject: Any {
+    fn get_property(&self, name: &str) -> Option<Value>;
+
+    /// Returns `None` when the object has no property called `name`.
+    fn set_property(&mut self, name: &str, value: &Value) -> Option<Result<(), ConversionError>>;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// A host object type that can be defined as a Lox class.
+pub trait LoxClass: HostObject {
+    const NAME: &'static str;
+
+    /// The methods of the class, with the constructor as `init`.
+    fn methods() -> Vec<NativeMethod>;
+}
+
+pub type NativeMethodFn = dyn Fn(&Rc<Instance>, &[Value]) -> Result<Value, RuntimeErrorType>;
+
+/// A method implemented in Rust.
+pub struct NativeMethod {
+    pub name: String,
+    pub arity: usize,
+    function: Rc<NativeMethodFn>,
+}
+
+impl NativeMethod {
+    pub fn new(
+        name: impl Into<String>,
+        arity: usize,
+        function: impl Fn(&Rc<Instance>, &[Value]) -> Result<Value, RuntimeErrorType> + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            arity,
+            function: Rc::new(function),
+        }
+    }
+
+    /// Creates a native function calling this method on `instance`.
+    pub fn bind(&self, instance: Rc<Instance>) -> NativeFunction {
+        let function = Rc::clone(&self.function);
+        NativeFunction::new(self.name.clone(), self.arity, move |args| {
+            function(&instance, args)
+        })
+    }
+}
+
+impl Debug for NativeMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NativeMethod")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Runs `f` on the host object of `instance`, failing if the instance was
+/// never given one of type `T` by its constructor.
+pub fn with_host<T: HostObject, R>(
+    instance: &Instance,
+    f: impl FnOnce(&mut T) -> R,
+) -> Result<R, RuntimeErrorType> {
+    instance
+        .with_host(f)
+        .ok_or_else(|| RuntimeErrorType::Native {
+            message: format!("`{}` instance has no host object", instance.class.name),
+        })
+}
+
+#[cfg(test)]
+mod tests;
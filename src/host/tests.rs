@@ -0,0 +1,134 @@
+use crate::{error::RuntimeErrorType, lox_methods, Interpreter, LoxClass, LoxError, Value};
+
+#[derive(LoxClass)]
+struct Counter {
+    count: u32,
+    #[lox(rename = "stepSize")]
+    step: u32,
+    #[lox(skip)]
+    #[allow(dead_code)]
+    secret: String,
+}
+
+#[lox_methods]
+impl Counter {
+    #[lox(constructor)]
+    fn new(start: u32) -> Self {
+        Self {
+            count: start,
+            step: 1,
+            secret: "hidden".into(),
+        }
+    }
+
+    #[lox]
+    fn increment(&mut self) -> u32 {
+        self.count += self.step;
+        self.count
+    }
+
+    #[lox(rename = "addTo")]
+    fn add_to(&self, other: f64) -> f64 {
+        other + f64::from(self.count)
+    }
+
+    #[lox]
+    fn check(&self, limit: u32) -> Result<bool, RuntimeErrorType> {
+        match self.count > limit {
+            true => Err(RuntimeErrorType::Native {
+                message: format!("count is over {}", limit),
+            }),
+            false => Ok(true),
+        }
+    }
+
+    #[allow(dead_code)]
+    fn not_exposed(&self) {}
+}
+
+fn counter() -> Interpreter {
+    let mut int = Interpreter::default();
+    int.define_class::<Counter>();
+    int
+}
+
+fn error(int: &mut Interpreter, source: &str) -> String {
+    match int.eval_str(source).unwrap_err().remove(0) {
+        LoxError::RuntimeError(error) => error.error_type.to_string(),
+        error => panic!("expected a runtime error, found {:?}", error),
+    }
+}
+
+#[test]
+fn test_host_class_methods() {
+    let mut int = counter();
+    assert_eq!(
+        int.eval_str("var c = Counter(5); c.increment(); c.increment();"),
+        Ok(Value::Number(7.0))
+    );
+    assert_eq!(int.eval_str("c.addTo(0.5);"), Ok(Value::Number(7.5)));
+    assert_eq!(int.eval_str("c.check(10);"), Ok(Value::Bool(true)));
+    assert_eq!(int.eval_str("Counter;").unwrap().to_string(), "Counter");
+    assert_eq!(
+        int.eval_str("c.increment;").unwrap().to_string(),
+        "<native fn increment>"
+    );
+    assert_eq!(error(&mut int, "c.check(1);"), "count is over 1");
+    assert_eq!(
+        error(&mut int, "c.not_exposed();"),
+        "Undefined property `not_exposed`"
+    );
+    assert_eq!(
+        error(&mut int, "Counter();"),
+        "Expected 1 arguments, found 0"
+    );
+}
+
+#[test]
+fn test_host_class_properties() {
+    let mut int = counter();
+    assert_eq!(
+        int.eval_str("var c = Counter(1); c.stepSize = 10; c.increment(); c.count;"),
+        Ok(Value::Number(11.0))
+    );
+    assert_eq!(
+        int.eval_str("c.label = \"clicks\"; c.label;"),
+        Ok(Value::String("clicks".into()))
+    );
+    assert_eq!(error(&mut int, "c.secret;"), "Undefined property `secret`");
+    assert_eq!(
+        error(&mut int, "c.count = \"many\";"),
+        "Expected number, found string"
+    );
+    assert_eq!(
+        error(&mut int, "c.count = -1;"),
+        "Cannot convert -1 to `u32`"
+    );
+}
+
+#[test]
+fn test_host_class_subclass() {
+    let mut int = counter();
+    assert_eq!(
+        int.eval_str(
+            r#"
+            class Double < Counter {
+                init(start) {
+                    super.init(start);
+                    this.stepSize = 2;
+                }
+            }
+            var d = Double(0);
+            d.increment();
+            d.increment();
+        "#
+        ),
+        Ok(Value::Number(4.0))
+    );
+
+    int.eval_str("class Unset < Counter { init() {} }").unwrap();
+    assert_eq!(
+        error(&mut int, "Unset().increment();"),
+        "`Unset` instance has no host object"
+    );
+}
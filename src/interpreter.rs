@@ -1,38 +1,362 @@
-use anyhow::{anyhow, bail, Result};
+use std::{
+    cell::Cell,
+    collections::BTreeMap,
+    fs,
+    io::{self, BufRead, Write},
+    path::Path,
+    rc::Rc,
+};
+
+use rustc_hash::FxHashMap;
 
 use crate::{
+    class::{Class, Instance, Method},
+    convert::{FromArgs, IntoLox},
+    diagnostic::Diagnostic,
     environment::Environment,
-    expression::{Assign, Binary, Expression, ExpressionVisitor, Grouping, Literal, Unary, Var},
+    error::{LoxError, ResolveError, ResolveErrorType, Result, RuntimeError, RuntimeErrorType},
+    expression::{
+        Assign, Binary, Call, Expression, ExpressionVisitor, Get, Grouping, Index, Interpolation,
+        List, Literal, LiteralValue, Map, Set, Super, This, Unary, Var,
+    },
+    function::Function,
+    host::LoxClass,
+    native::{self, Input, NativeFunction},
     operator::{BinaryOperator, UnaryOperator},
-    statement::{BlockStmt, IfStmt, Stmt, StmtVisitor, VarDecl, WhileStmt},
-    value::Value,
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+    span::Span,
+    statement::{
+        BlockStmt, BreakStmt, ClassDecl, ContinueStmt, ExprStmt, FunDecl, IfStmt, PrintStmt,
+        ReturnStmt, Stmt, StmtVisitor, VarDecl, WhileStmt,
+    },
+    value::{MapKey, Value},
 };
 
-#[derive(Default)]
+/// How deeply Lox calls may nest before raising a stack overflow, rather
+/// than overflowing the native stack.
+const MAX_CALL_DEPTH: usize = 1024;
+
 pub struct Interpreter {
+    globals: Environment,
     env: Environment,
+    call_depth: usize,
+    stdout: Box<dyn Write>,
+    stderr: Box<dyn Write>,
+    stdin: Input,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        let globals = Environment::default();
+        for native in native::defaults().into_iter().map(Rc::new) {
+            globals.define(&native.name, Value::Native(Rc::clone(&native)));
+        }
+        let stdin = Input::default();
+        let read_line = native::read_line(Rc::clone(&stdin));
+        globals.define("readLine", Value::Native(Rc::new(read_line)));
+
+        let env = globals.clone();
+        Self {
+            globals,
+            env,
+            call_depth: 0,
+            stdout: Box::new(io::stdout()),
+            stderr: Box::new(io::stderr()),
+            stdin,
+        }
+    }
+}
+
+/// How control leaves a statement.
+pub enum ControlFlow {
+    Normal,
+    Return(Value),
+    /// Leaves the loop with this label, or the innermost one.
+    Break(Option<String>),
+    Continue(Option<String>),
 }
 
 impl Interpreter {
+    /// Sends the output of `print` to `stdout`.
+    pub fn with_stdout(mut self, stdout: impl Write + 'static) -> Self {
+        self.stdout = Box::new(stdout);
+        self
+    }
+
+    /// Sends reported errors to `stderr`.
+    pub fn with_stderr(mut self, stderr: impl Write + 'static) -> Self {
+        self.stderr = Box::new(stderr);
+        self
+    }
+
+    /// Makes `readLine()` read from `stdin`.
+    pub fn with_stdin(self, stdin: impl BufRead + 'static) -> Self {
+        *self.stdin.borrow_mut() = Some(Box::new(stdin));
+        self
+    }
+
+    /// Writes `errors` to stderr as diagnostics pointing into `source`.
+    pub fn report(&mut self, errors: &[LoxError], source: &str) -> io::Result<()> {
+        for error in errors {
+            let diagnostic = Diagnostic::from(error);
+            self.stderr
+                .write_all(diagnostic.render(source, false).as_bytes())?;
+        }
+        self.stderr.flush()
+    }
+
+    /// Runs `source` as a program, returning the value of its final
+    /// expression statement, or `nil` if it does not end in one.
+    pub fn eval_str(&mut self, source: &str) -> Result<Value, Vec<LoxError>> {
+        let tokens = Scanner::new(source.into()).scan_source()?;
+        let stmts = Parser::new(tokens).parse()?;
+        Resolver::default().resolve(&stmts)?;
+
+        let Some((last, init)) = stmts.split_last() else {
+            return Ok(Value::Nil);
+        };
+        let run = |int: &mut Self| {
+            for stmt in init {
+                int.interpret(stmt)?;
+            }
+            match last {
+                Stmt::ExprStmt(stmt) => int.evaluate(&stmt.expr),
+                stmt => int.interpret(stmt).map(|()| Value::Nil),
+            }
+        };
+        run(self).map_err(|e| vec![e])
+    }
+
+    /// Reads and runs the program at `path`. The outer result reports
+    /// failures to read the file, the inner one the outcome of running it.
+    pub fn run_file(&mut self, path: impl AsRef<Path>) -> io::Result<Result<Value, Vec<LoxError>>> {
+        let source = fs::read_to_string(path)?;
+        Ok(self.eval_str(&source))
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.globals.get(name)
+    }
+
+    /// Every global variable, sorted by name.
+    pub fn globals(&self) -> Vec<(String, Value)> {
+        let mut globals = self.globals.values();
+        globals.sort_by(|(a, _), (b, _)| a.cmp(b));
+        globals
+    }
+
+    pub fn define_global(&mut self, name: &str, value: Value) {
+        self.globals.define(name, value);
+    }
+
+    /// Defines a global function `name` that runs `function` when called
+    /// with `arity` arguments.
+    pub fn define_native(
+        &mut self,
+        name: &str,
+        arity: usize,
+        function: impl Fn(&[Value]) -> Result<Value, RuntimeErrorType> + 'static,
+    ) {
+        let native = NativeFunction::new(name, arity, function);
+        self.define_global(name, Value::Native(Rc::new(native)));
+    }
+
+    /// Like [`define_native`](Self::define_native), but for a function over
+    /// Rust types. Its arity is the length of the `Args` tuple.
+    pub fn define_fn<Args: FromArgs, R: IntoLox>(
+        &mut self,
+        name: &str,
+        function: impl Fn(Args) -> Result<R, RuntimeErrorType> + 'static,
+    ) {
+        let native = NativeFunction::from_fn(name, function);
+        self.define_global(name, Value::Native(Rc::new(native)));
+    }
+
+    /// Defines the host class `T` as a global, callable through its
+    /// constructor.
+    pub fn define_class<T: LoxClass>(&mut self) {
+        let methods = T::methods()
+            .into_iter()
+            .map(|method| (method.name.clone(), Method::Native(Rc::new(method))))
+            .collect();
+        let class = Class::new(T::NAME.into(), None, methods);
+        self.define_global(T::NAME, Value::Class(Rc::new(class)));
+    }
+
     fn evaluate(&mut self, expr: &Expression) -> Result<Value> {
         expr.accept(self)
     }
 
+    fn execute(&mut self, stmt: &Stmt) -> Result<ControlFlow> {
+        stmt.accept(self)
+    }
+
+    /// Executes statements in the current scope, stopping early on `return`,
+    /// `break` or `continue`.
+    fn execute_stmts(&mut self, stmts: &[Stmt]) -> Result<ControlFlow> {
+        for stmt in stmts {
+            match self.execute(stmt)? {
+                ControlFlow::Normal => {}
+                flow => return Ok(flow),
+            }
+        }
+        Ok(ControlFlow::Normal)
+    }
+
+    /// Executes statements in `env`, restoring the current environment afterwards.
+    fn execute_block(&mut self, stmts: &[Stmt], env: Environment) -> Result<ControlFlow> {
+        let previous = std::mem::replace(&mut self.env, env);
+        let result = self.execute_stmts(stmts);
+        self.env = previous;
+        result
+    }
+
+    /// Looks up a variable at the depth recorded by the resolver.
+    fn look_up(&self, name: &str, depth: &Cell<Option<usize>>) -> Option<Value> {
+        match depth.get() {
+            Some(distance) => self.env.get_at(distance, name),
+            None => self.globals.get(name),
+        }
+    }
+
+    fn call_function(&mut self, function: &Function, arguments: Vec<Value>) -> Result<Value> {
+        let env = Environment::new_enclosed(&function.closure);
+        for (param, arg) in function.declaration.params.iter().zip(arguments) {
+            env.define(param, arg);
+        }
+
+        let result = self.execute_block(&function.declaration.body, env)?;
+        if function.is_initializer {
+            return Ok(function.closure.get("this").unwrap());
+        }
+        match result {
+            ControlFlow::Return(value) => Ok(value),
+            // The resolver keeps `break` and `continue` inside loops.
+            ControlFlow::Normal | ControlFlow::Break(_) | ControlFlow::Continue(_) => {
+                Ok(Value::Nil)
+            }
+        }
+    }
+
+    fn evaluate_superclass(&mut self, superclass: &Var) -> Result<Rc<Class>> {
+        match self.visit_var(superclass)? {
+            Value::Class(superclass) => Ok(superclass),
+            _ => Err(RuntimeError {
+                error_type: RuntimeErrorType::SuperclassNotClass {
+                    name: superclass.name.clone(),
+                },
+                span: superclass.span,
+            }
+            .into()),
+        }
+    }
+
+    fn instantiate(
+        &mut self,
+        class: Rc<Class>,
+        arguments: Vec<Value>,
+        span: Span,
+    ) -> Result<Value> {
+        let instance = Rc::new(Instance::new(Rc::clone(&class)));
+        if let Some(init) = bind_method(&class, "init", Rc::clone(&instance)) {
+            self.call(init, arguments, span)?;
+        }
+        Ok(Value::Instance(instance))
+    }
+
+    /// Runs `call` one level deeper, failing once calls nest too deeply.
+    fn nested_call(
+        &mut self,
+        span: Span,
+        call: impl FnOnce(&mut Self) -> Result<Value>,
+    ) -> Result<Value> {
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(RuntimeError {
+                error_type: RuntimeErrorType::StackOverflow,
+                span,
+            }
+            .into());
+        }
+        self.call_depth += 1;
+        let result = call(self);
+        self.call_depth -= 1;
+        result
+    }
+
+    fn call(&mut self, callee: Value, arguments: Vec<Value>, span: Span) -> Result<Value> {
+        let arity = match &callee {
+            Value::Function(function) => function.arity(),
+            Value::Class(class) => class.arity(),
+            Value::Native(native) => native.arity,
+            _ => {
+                return Err(RuntimeError {
+                    error_type: RuntimeErrorType::NotCallable,
+                    span,
+                }
+                .into())
+            }
+        };
+        if arguments.len() != arity {
+            return Err(RuntimeError {
+                error_type: RuntimeErrorType::ArityMismatch {
+                    expected: arity,
+                    found: arguments.len(),
+                },
+                span,
+            }
+            .into());
+        }
+
+        match callee {
+            Value::Function(function) => {
+                self.nested_call(span, |int| int.call_function(&function, arguments))
+            }
+            Value::Class(class) => {
+                self.nested_call(span, |int| int.instantiate(class, arguments, span))
+            }
+            Value::Native(native) => native
+                .call(&arguments)
+                .map_err(|error_type| RuntimeError { error_type, span }.into()),
+            _ => unreachable!(),
+        }
+    }
+
     pub fn interpret(&mut self, stmt: &Stmt) -> Result<()> {
-        stmt.accept(&mut *self)?;
-        Ok(())
+        let error_type = match self.execute(stmt)? {
+            ControlFlow::Normal => return Ok(()),
+            ControlFlow::Return(_) => ResolveErrorType::TopLevelReturn,
+            ControlFlow::Break(_) => ResolveErrorType::BreakOutsideLoop,
+            ControlFlow::Continue(_) => ResolveErrorType::ContinueOutsideLoop,
+        };
+        Err(ResolveError {
+            error_type,
+            span: stmt.span(),
+        }
+        .into())
+    }
+}
+
+/// Looks up a method declared by the tree-walker or the host and binds it to
+/// `instance`.
+fn bind_method(class: &Class, name: &str, instance: Rc<Instance>) -> Option<Value> {
+    match class.find_method(name)? {
+        Method::Function(function) => Some(Value::Function(Rc::new(function.bind(instance)))),
+        Method::Native(method) => Some(Value::Native(Rc::new(method.bind(instance)))),
+        Method::Closure(_) => unreachable!("Bytecode classes never reach the tree-walker"),
     }
 }
 
 impl ExpressionVisitor for Interpreter {
     type Return = Result<Value>;
     fn visit_literal(&mut self, inner: &Literal) -> Self::Return {
-        let val = match *inner {
-            Literal::String(ref s) => Value::String(s.clone()),
-            Literal::Number(n) => Value::Number(n),
-            Literal::True => Value::Bool(true),
-            Literal::False => Value::Bool(false),
-            Literal::Nil => Value::Nil,
+        let val = match inner.value {
+            LiteralValue::String(ref s) => Value::String(s.clone()),
+            LiteralValue::Number(n) => Value::Number(n),
+            LiteralValue::True => Value::Bool(true),
+            LiteralValue::False => Value::Bool(false),
+            LiteralValue::Nil => Value::Nil,
         };
         Ok(val)
     }
@@ -40,8 +364,16 @@ impl ExpressionVisitor for Interpreter {
     fn visit_unary(&mut self, inner: &Unary) -> Self::Return {
         match inner.operator {
             UnaryOperator::Minus => {
-                let Value::Number(n) = self.evaluate(&inner.expr)? else {
-                    bail!("Expected a number, found {:?}", inner.expr)
+                let value = self.evaluate(&inner.expr)?;
+                let Value::Number(n) = value else {
+                    return Err(RuntimeError {
+                        error_type: RuntimeErrorType::TypeMismatch {
+                            expected: "number",
+                            actual: value.type_name(),
+                        },
+                        span: inner.span,
+                    }
+                    .into());
                 };
                 Ok(Value::Number(-n))
             }
@@ -72,18 +404,24 @@ impl ExpressionVisitor for Interpreter {
         }
 
         let right = self.evaluate(&inner.right)?;
+        let at_span = |error_type| {
+            LoxError::from(RuntimeError {
+                error_type,
+                span: inner.span,
+            })
+        };
         match inner.operator {
-            BinaryOperator::Minus => Ok((left - right)?),
-            BinaryOperator::Plus => Ok((left + right)?),
-            BinaryOperator::Mult => Ok((left * right)?),
-            BinaryOperator::Div => Ok((left / right)?),
+            BinaryOperator::Minus => (left - right).map_err(at_span),
+            BinaryOperator::Plus => (left + right).map_err(at_span),
+            BinaryOperator::Mult => (left * right).map_err(at_span),
+            BinaryOperator::Div => (left / right).map_err(at_span),
             BinaryOperator::EqualEqual => Ok(Value::Bool(left == right)),
             BinaryOperator::NotEqual => Ok(Value::Bool(left != right)),
-            BinaryOperator::Less => Ok(Value::Bool(left < right)),
-            BinaryOperator::LessEqual => Ok(Value::Bool(left <= right)),
-            BinaryOperator::Greater => Ok(Value::Bool(left > right)),
-            BinaryOperator::GreaterEqual => Ok(Value::Bool(left >= right)),
-            op => bail!("Unexpected operator: {}", op),
+            BinaryOperator::Less => left.compare(&right, Value::lt).map_err(at_span),
+            BinaryOperator::LessEqual => left.compare(&right, Value::le).map_err(at_span),
+            BinaryOperator::Greater => left.compare(&right, Value::gt).map_err(at_span),
+            BinaryOperator::GreaterEqual => left.compare(&right, Value::ge).map_err(at_span),
+            op => unreachable!("unexpected binary operator {}", op),
         }
     }
 
@@ -92,32 +430,186 @@ impl ExpressionVisitor for Interpreter {
     }
 
     fn visit_var(&mut self, inner: &Var) -> Self::Return {
-        self.env
-            .get(&inner.name)
-            .cloned()
-            .ok_or_else(|| anyhow!("{} is not defined.", inner.name))
+        self.look_up(&inner.name, &inner.depth).ok_or_else(|| {
+            RuntimeError {
+                error_type: RuntimeErrorType::UndefinedVariable {
+                    name: inner.name.clone(),
+                },
+                span: inner.span,
+            }
+            .into()
+        })
     }
 
     fn visit_assign(&mut self, inner: &Assign) -> Self::Return {
-        let target = inner.name.as_ref();
-        let Expression::Var(Var { name }) = target else {
-            bail!("Cannot assign to {:?}", target)
+        match inner.target.as_ref() {
+            Expression::Var(Var { name, depth, .. }) => {
+                let value = self.evaluate(&inner.value)?;
+                match depth.get() {
+                    Some(distance) => Ok(self.env.assign_at(distance, name, value)),
+                    None => self.globals.assign(name, value, inner.span),
+                }
+            }
+            Expression::Index(target) => {
+                let object = self.evaluate(&target.object)?;
+                let index = self.evaluate(&target.index)?;
+                let value = self.evaluate(&inner.value)?;
+                object
+                    .set_index(&index, value.clone())
+                    .map_err(|error_type| RuntimeError {
+                        error_type,
+                        span: target.span,
+                    })?;
+                Ok(value)
+            }
+            _ => unreachable!("the parser only produces variable and index assignment targets"),
+        }
+    }
+
+    fn visit_list(&mut self, inner: &List) -> Self::Return {
+        let elements = inner
+            .elements
+            .iter()
+            .map(|element| self.evaluate(element))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Value::list(elements))
+    }
+
+    fn visit_map(&mut self, inner: &Map) -> Self::Return {
+        let mut entries = BTreeMap::new();
+        for (key, value) in &inner.entries {
+            let span = key.span();
+            let key = MapKey::try_from(&self.evaluate(key)?)
+                .map_err(|error_type| RuntimeError { error_type, span })?;
+            entries.insert(key, self.evaluate(value)?);
+        }
+        Ok(Value::map(entries))
+    }
+
+    fn visit_interpolation(&mut self, inner: &Interpolation) -> Self::Return {
+        let mut string = String::new();
+        for part in &inner.parts {
+            string.push_str(&self.evaluate(part)?.to_string());
+        }
+        Ok(Value::String(string))
+    }
+
+    fn visit_index(&mut self, inner: &Index) -> Self::Return {
+        let object = self.evaluate(&inner.object)?;
+        let index = self.evaluate(&inner.index)?;
+        object.get_index(&index).map_err(|error_type| {
+            RuntimeError {
+                error_type,
+                span: inner.span,
+            }
+            .into()
+        })
+    }
+
+    fn visit_call(&mut self, inner: &Call) -> Self::Return {
+        let callee = self.evaluate(&inner.callee)?;
+        let arguments = inner
+            .arguments
+            .iter()
+            .map(|arg| self.evaluate(arg))
+            .collect::<Result<Vec<_>>>()?;
+        self.call(callee, arguments, inner.span)
+    }
+
+    fn visit_get(&mut self, inner: &Get) -> Self::Return {
+        let Value::Instance(instance) = self.evaluate(&inner.object)? else {
+            return Err(RuntimeError {
+                error_type: RuntimeErrorType::NotAnInstance,
+                span: inner.span,
+            }
+            .into());
+        };
+
+        if let Some(value) = instance.get_field(&inner.name) {
+            return Ok(value);
+        }
+        match bind_method(&instance.class, &inner.name, Rc::clone(&instance)) {
+            Some(method) => Ok(method),
+            None => Err(RuntimeError {
+                error_type: RuntimeErrorType::UndefinedProperty {
+                    name: inner.name.clone(),
+                },
+                span: inner.span,
+            }
+            .into()),
+        }
+    }
+
+    fn visit_set(&mut self, inner: &Set) -> Self::Return {
+        let Value::Instance(instance) = self.evaluate(&inner.object)? else {
+            return Err(RuntimeError {
+                error_type: RuntimeErrorType::NotAnInstance,
+                span: inner.span,
+            }
+            .into());
         };
+
         let value = self.evaluate(&inner.value)?;
-        self.env.assign(name, value)
+        instance
+            .set_field(&inner.name, value.clone())
+            .map_err(|e| RuntimeError {
+                error_type: e.into(),
+                span: inner.span,
+            })?;
+        Ok(value)
+    }
+
+    fn visit_this(&mut self, inner: &This) -> Self::Return {
+        self.look_up("this", &inner.depth).ok_or_else(|| {
+            ResolveError {
+                error_type: ResolveErrorType::ThisOutsideClass,
+                span: inner.span,
+            }
+            .into()
+        })
+    }
+
+    fn visit_super(&mut self, inner: &Super) -> Self::Return {
+        // `this` is always bound in the scope just inside the one binding `super`.
+        let this_depth = Cell::new(inner.depth.get().map(|d| d - 1));
+        let (Some(Value::Class(superclass)), Some(Value::Instance(instance))) = (
+            self.look_up("super", &inner.depth),
+            self.look_up("this", &this_depth),
+        ) else {
+            return Err(ResolveError {
+                error_type: ResolveErrorType::SuperOutsideClass,
+                span: inner.span,
+            }
+            .into());
+        };
+
+        match bind_method(&superclass, &inner.method, instance) {
+            Some(method) => Ok(method),
+            None => Err(RuntimeError {
+                error_type: RuntimeErrorType::UndefinedProperty {
+                    name: inner.method.clone(),
+                },
+                span: inner.span,
+            }
+            .into()),
+        }
     }
 }
 
 impl StmtVisitor for Interpreter {
-    type Return = Result<()>;
-    fn visit_expr_stmt(&mut self, inner: &Expression) -> Self::Return {
-        self.evaluate(inner)?;
-        Ok(())
+    type Return = Result<ControlFlow>;
+    fn visit_expr_stmt(&mut self, inner: &ExprStmt) -> Self::Return {
+        self.evaluate(&inner.expr)?;
+        Ok(ControlFlow::Normal)
     }
 
-    fn visit_print_stmt(&mut self, inner: &Expression) -> Self::Return {
-        println!("{}", self.evaluate(inner)?);
-        Ok(())
+    fn visit_print_stmt(&mut self, inner: &PrintStmt) -> Self::Return {
+        let value = self.evaluate(&inner.expr)?;
+        writeln!(self.stdout, "{}", value).map_err(|e| RuntimeError {
+            error_type: e.into(),
+            span: inner.span,
+        })?;
+        Ok(ControlFlow::Normal)
     }
 
     fn visit_var_decl(&mut self, inner: &VarDecl) -> Self::Return {
@@ -127,36 +619,96 @@ impl StmtVisitor for Interpreter {
         };
         self.env.define(&inner.name, value);
 
-        Ok(())
+        Ok(ControlFlow::Normal)
     }
 
-    fn visit_block_stmt(&mut self, inner: &BlockStmt) -> Self::Return {
-        self.env.push_scope();
-        for stmt in &inner.stmts {
-            self.interpret(stmt)?;
-        }
-        self.env.pop_scope();
+    fn visit_fun_decl(&mut self, inner: &Rc<FunDecl>) -> Self::Return {
+        let function = Function::new(Rc::clone(inner), self.env.clone(), false);
+        self.env
+            .define(&inner.name, Value::Function(Rc::new(function)));
+
+        Ok(ControlFlow::Normal)
+    }
+
+    fn visit_class_decl(&mut self, inner: &ClassDecl) -> Self::Return {
+        let superclass = match &inner.superclass {
+            Some(var) => Some(self.evaluate_superclass(var)?),
+            None => None,
+        };
+
+        // Methods of a subclass close over a scope that binds `super`.
+        let method_env = match &superclass {
+            Some(superclass) => {
+                let env = Environment::new_enclosed(&self.env);
+                env.define("super", Value::Class(Rc::clone(superclass)));
+                env
+            }
+            None => self.env.clone(),
+        };
+
+        let methods = inner
+            .methods
+            .iter()
+            .map(|method| {
+                let is_initializer = method.name == "init";
+                let function = Function::new(Rc::clone(method), method_env.clone(), is_initializer);
+                (method.name.clone(), Method::Function(Rc::new(function)))
+            })
+            .collect::<FxHashMap<_, _>>();
+        let class = Class::new(inner.name.clone(), superclass, methods);
+        self.env.define(&inner.name, Value::Class(Rc::new(class)));
+
+        Ok(ControlFlow::Normal)
+    }
 
-        Ok(())
+    fn visit_block_stmt(&mut self, inner: &BlockStmt) -> Self::Return {
+        let env = Environment::new_enclosed(&self.env);
+        self.execute_block(&inner.stmts, env)
     }
 
     fn visit_if_stmt(&mut self, inner: &IfStmt) -> Self::Return {
         let condition = self.evaluate(inner.condition.as_ref())?;
         if condition.is_truthy() {
-            self.interpret(inner.then_branch.as_ref())?;
+            self.execute(inner.then_branch.as_ref())
         } else if let Some(s) = &inner.else_branch {
-            self.interpret(s.as_ref())?;
+            self.execute(s.as_ref())
+        } else {
+            Ok(ControlFlow::Normal)
         }
-
-        Ok(())
     }
 
     fn visit_while_stmt(&mut self, inner: &WhileStmt) -> Self::Return {
         while self.evaluate(inner.condition.as_ref())?.is_truthy() {
-            self.interpret(inner.body.as_ref())?;
+            let targets_this = |label: &Option<String>| label.is_none() || *label == inner.label;
+            match self.execute(inner.body.as_ref())? {
+                ControlFlow::Normal => {}
+                ControlFlow::Continue(label) if targets_this(&label) => {}
+                ControlFlow::Break(label) if targets_this(&label) => break,
+                flow => return Ok(flow),
+            }
+            if let Some(increment) = &inner.increment {
+                self.evaluate(increment)?;
+            }
         }
 
-        Ok(())
+        Ok(ControlFlow::Normal)
+    }
+
+    fn visit_return(&mut self, inner: &ReturnStmt) -> Self::Return {
+        let value = match &inner.value {
+            Some(e) => self.evaluate(e.as_ref())?,
+            None => Value::Nil,
+        };
+
+        Ok(ControlFlow::Return(value))
+    }
+
+    fn visit_break(&mut self, inner: &BreakStmt) -> Self::Return {
+        Ok(ControlFlow::Break(inner.label.clone()))
+    }
+
+    fn visit_continue(&mut self, inner: &ContinueStmt) -> Self::Return {
+        Ok(ControlFlow::Continue(inner.label.clone()))
     }
 }
 
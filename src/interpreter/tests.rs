@@ -1,26 +1,60 @@
-use crate::{parser::Parser, scanner::Scanner, statement::Stmt};
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+};
+
+use crate::{parser::Parser, resolver::Resolver, scanner::Scanner, span::Span, statement::Stmt};
 
 use super::*;
 
-impl Interpreter {
-    fn get_var(&self, name: &str) -> &Value {
-        self.env.get(name).unwrap()
+/// A sink that stays readable after the interpreter takes ownership of it.
+#[derive(Clone, Default)]
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Output {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
     }
 }
 
+/// Runs `source` with `input` as stdin, returning what it printed.
+fn run_with_input(source: &str, input: &'static str) -> String {
+    let stdout = Output::default();
+    let mut int = Interpreter::default()
+        .with_stdout(stdout.clone())
+        .with_stdin(input.as_bytes());
+    int.eval_str(source).unwrap();
+    stdout.contents()
+}
+
+fn run(source: &str) -> String {
+    run_with_input(source, "")
+}
+
 fn evaluate_expr(source: &'static str) -> Value {
     let mut int = Interpreter::default();
     let tokens = Scanner::new(source.into()).scan_source().unwrap();
     let stmts = Parser::new(tokens).parse().unwrap();
-    let Stmt::ExprStmt(ref expr) = stmts[0] else {
+    let Stmt::ExprStmt(ref stmt) = stmts[0] else {
         panic!()
     };
-    int.evaluate(&expr).unwrap()
+    int.evaluate(&stmt.expr).unwrap()
 }
 
 fn interpret_stmts(source: &'static str, interpreter: &mut Interpreter) {
     let tokens = Scanner::new(source.into()).scan_source().unwrap();
     let stmts = Parser::new(tokens).parse().unwrap();
+    Resolver::default().resolve(&stmts).unwrap();
 
     for stmt in &stmts {
         interpreter.interpret(stmt).unwrap();
@@ -54,38 +88,619 @@ fn test_error() {
 
 #[test]
 fn test_int_expr_stmt() {
-    let mut int = Interpreter::default();
-    interpret_stmts("var x = 5 + 5; x = x + 1;", &mut int);
-    assert_eq!(int.get_var("x"), &Value::Number(11.0));
+    assert_eq!(run("var x = 5 + 5; x = x + 1; print x;"), "11\n");
 }
 
 #[test]
 fn test_int_if_stmt() {
-    let mut int = Interpreter::default();
-    interpret_stmts(
-        r#"
+    let output = run(r#"
         var x = 5;
         if (x > 10)
-            x = x * 2;
+            print "big";
         else
-            x = x * 3;
-    "#,
-        &mut int,
-    );
-    assert_eq!(int.get_var("x"), &Value::Number(15.0));
+            print "small";
+        if (x == 5) print x * 3;
+    "#);
+    assert_eq!(output, "small\n15\n");
 }
 
 #[test]
 fn test_int_while_stmt() {
-    let mut int = Interpreter::default();
-    interpret_stmts(
-        r#"
+    let output = run(r#"
         var x = 0;
-        while (x < 10) {
+        while (x < 3) {
+            print x;
             x = x + 1;
         }
+    "#);
+    assert_eq!(output, "0\n1\n2\n");
+}
+
+#[test]
+fn test_int_break_continue() {
+    let output = run(r#"
+        for (var i = 0; i < 10; i = i + 1) {
+            if (i == 1) continue;
+            if (i == 4) break;
+            var j = 0;
+            while (true) {
+                j = j + 1;
+                if (j < i) continue;
+                break;
+            }
+            print j;
+        }
+    "#);
+    assert_eq!(output, "1\n2\n3\n");
+}
+
+#[test]
+fn test_int_labeled_loops() {
+    let output = run(r#"
+        var grid = [[1, 2], [3, 4], [5, 6]];
+        search: for (var i = 0; i < len(grid); i = i + 1) {
+            var j = 0;
+            row: while (j < 2) {
+                var cell = grid[i][j];
+                j = j + 1;
+                if (cell == 2) continue search;
+                if (cell == 3) continue row;
+                if (cell == 5) break search;
+                print cell;
+            }
+        }
+    "#);
+    assert_eq!(output, "1\n4\n");
+}
+
+#[test]
+fn test_int_fun_decl() {
+    let output = run(r#"
+        fun fib(n) {
+            if (n <= 1) return n;
+            return fib(n - 2) + fib(n - 1);
+        }
+        print fib(10);
+        print fib;
+    "#);
+    assert_eq!(output, "55\n<fn fib>\n");
+}
+
+#[test]
+fn test_int_stack_overflow() {
+    // Test threads get less stack than the main thread the binary runs on.
+    let thread = std::thread::Builder::new().stack_size(8 << 20);
+    let handle = thread
+        .spawn(|| {
+            let mut int = Interpreter::default();
+            let errors = int
+                .eval_str("fun f(n) { return f(n + 1); } f(0);")
+                .unwrap_err();
+            assert!(matches!(
+                &errors[..],
+                [LoxError::RuntimeError(RuntimeError {
+                    error_type: RuntimeErrorType::StackOverflow,
+                    ..
+                })]
+            ));
+            // The depth unwinds with the error.
+            assert_eq!(
+                int.eval_str("fun g(n) { if (n > 0) return g(n - 1); return n; } g(100);"),
+                Ok(Value::Number(0.0))
+            );
+        })
+        .unwrap();
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_int_return_unwinds_scopes() {
+    let output = run(r#"
+        var x = "global";
+        fun find(limit) {
+            var i = 0;
+            while (true) {
+                var x = i * 2;
+                if (x >= limit) {
+                    return i;
+                }
+                i = i + 1;
+            }
+        }
+        print find(7);
+        print x;
+    "#);
+    assert_eq!(output, "4\nglobal\n");
+}
+
+#[test]
+#[should_panic]
+fn test_int_arity_mismatch() {
+    let mut int = Interpreter::default();
+    interpret_stmts("fun f(a, b) { return a + b; } f(1);", &mut int);
+}
+
+#[test]
+fn test_int_closure() {
+    let output = run(r#"
+        fun makeCounter() {
+            var i = 0;
+            fun count() {
+                i = i + 1;
+                return i;
+            }
+            return count;
+        }
+        var a = makeCounter();
+        var b = makeCounter();
+        a();
+        a();
+        print a();
+        print b();
+    "#);
+    assert_eq!(output, "3\n1\n");
+}
+
+#[test]
+fn test_int_class() {
+    let output = run(r#"
+        class Counter {
+            init(start) {
+                this.count = start;
+            }
+
+            increment() {
+                this.count = this.count + 1;
+                return this;
+            }
+        }
+        var counter = Counter(10);
+        counter.increment().increment();
+        var increment = counter.increment;
+        increment();
+        print counter.count;
+        counter.label = "clicks";
+        print counter.label;
+        print counter;
+        print Counter;
+    "#);
+    assert_eq!(output, "13\nclicks\nCounter instance\nCounter\n");
+}
+
+#[test]
+#[should_panic]
+fn test_int_undefined_property() {
+    let mut int = Interpreter::default();
+    interpret_stmts("class A {} var a = A(); a.missing;", &mut int);
+}
+
+#[test]
+fn test_int_inheritance() {
+    let output = run(r#"
+        class Animal {
+            init(name) {
+                this.name = name;
+            }
+
+            speak() {
+                return this.name + " makes a sound";
+            }
+
+            describe() {
+                return "animal";
+            }
+        }
+        class Dog < Animal {
+            speak() {
+                return super.speak() + " (woof)";
+            }
+        }
+        class Puppy < Dog {}
+        var p = Puppy("Rex");
+        print p.speak();
+        print p.describe();
+    "#);
+    assert_eq!(output, "Rex makes a sound (woof)\nanimal\n");
+}
+
+#[test]
+fn test_int_inheritance_errors() {
+    let run = |source: &'static str| {
+        let mut int = Interpreter::default();
+        let tokens = Scanner::new(source.into()).scan_source().unwrap();
+        let stmts = Parser::new(tokens).parse().unwrap();
+        Resolver::default().resolve(&stmts).unwrap();
+        stmts
+            .iter()
+            .try_for_each(|stmt| int.interpret(stmt))
+            .unwrap_err()
+            .to_string()
+    };
+    assert_eq!(
+        run("var A = 1;\nclass B < A {}"),
+        "line 2:11: Superclass `A` must be a class"
+    );
+}
+
+#[test]
+fn test_int_runtime_error_types() {
+    let run = |source: &'static str| {
+        let mut int = Interpreter::default();
+        let tokens = Scanner::new(source.into()).scan_source().unwrap();
+        let stmts = Parser::new(tokens).parse().unwrap();
+        match stmts.iter().try_for_each(|stmt| int.interpret(stmt)) {
+            Err(LoxError::RuntimeError(error)) => error,
+            result => panic!("expected a runtime error, found {:?}", result),
+        }
+    };
+    assert_eq!(
+        run("print missing;"),
+        RuntimeError {
+            error_type: RuntimeErrorType::UndefinedVariable {
+                name: "missing".into()
+            },
+            span: Span::new(6, 13, 1, 7),
+        }
+    );
+    assert_eq!(
+        run("-\"text\";").error_type,
+        RuntimeErrorType::TypeMismatch {
+            expected: "number",
+            actual: "string"
+        }
+    );
+    assert_eq!(
+        run("true - 1;").error_type,
+        RuntimeErrorType::TypeMismatch {
+            expected: "number",
+            actual: "bool"
+        }
+    );
+}
+
+#[test]
+fn test_int_resolved_closure_binding() {
+    let output = run(r#"
+        var a = "global";
+        {
+            fun showA() {
+                print a;
+            }
+            showA();
+            var a = "block";
+            showA();
+        }
+    "#);
+    assert_eq!(output, "global\nglobal\n");
+}
+
+#[test]
+fn test_eval_str() {
+    let mut int = Interpreter::default();
+    assert_eq!(int.eval_str("var a = 2;"), Ok(Value::Nil));
+    assert_eq!(int.eval_str("a = a * 3; a + 1;"), Ok(Value::Number(7.0)));
+    assert_eq!(int.get_global("a"), Some(Value::Number(6.0)));
+
+    int.define_global("name", Value::String("lox".into()));
+    assert_eq!(
+        int.eval_str("\"hello \" + name;"),
+        Ok(Value::String("hello lox".into()))
+    );
+
+    let errors = int.eval_str("print ; print missing;").unwrap_err();
+    assert!(matches!(errors[..], [LoxError::ParseError(_)]));
+    let errors = int.eval_str("print missing;").unwrap_err();
+    assert!(matches!(errors[..], [LoxError::RuntimeError(_)]));
+}
+
+#[test]
+fn test_globals() {
+    let mut int = Interpreter::default();
+    int.eval_str("var b = 1; var a = \"x\";").unwrap();
+    let names = int
+        .globals()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "a", "b", "clock", "has", "keys", "len", "pop", "push", "readLine", "remove", "slice",
+            "values"
+        ]
+    );
+}
+
+#[test]
+fn test_run_file() {
+    let path = std::env::temp_dir().join("rlox_test_run_file.lox");
+    std::fs::write(&path, "fun square(n) { return n * n; }\nsquare(4);\n").unwrap();
+    let mut int = Interpreter::default();
+    assert_eq!(int.run_file(&path).unwrap(), Ok(Value::Number(16.0)));
+    assert!(int.get_global("square").is_some());
+    assert!(int.run_file("missing.lox").is_err());
+}
+
+#[test]
+fn test_lists() {
+    let output = run(r#"
+        var xs = [1, "two", [3]];
+        print xs;
+        print xs[1] + xs[2][0];
+        var ys = xs;
+        ys[0] = xs[0] + 10;
+        print xs[0];
+        push(xs, nil);
+        print len(xs);
+        print pop(xs);
+        print slice(xs, 1, 3);
+        print slice(xs, 2, 1);
+        print len("héllo");
+        print [1, 2] == [1, 2];
+    "#);
+    assert_eq!(
+        output,
+        "[1, \"two\", [3]]\ntwo3\n11\n4\nnil\n[\"two\", [3]]\n[]\n5\ntrue\n"
+    );
+}
+
+#[test]
+fn test_self_containing_collections() {
+    let output = run(r#"
+        var xs = [1];
+        push(xs, xs);
+        print xs;
+        print xs == xs;
+        print [xs, xs];
+        var m = {"a": xs};
+        m["self"] = m;
+        print m;
+        print m == m;
+        print [1, [2]] == [1, [2]];
+        var ys = [1];
+        push(ys, ys);
+        print xs == ys;
+        push(ys, 2);
+        print xs == ys;
+        var n = {"a": xs};
+        n["self"] = n;
+        print m == n;
+        n["a"] = 1;
+        print m == n;
+    "#);
+    assert_eq!(
+        output,
+        "[1, [...]]\ntrue\n[[1, [...]], [1, [...]]]\n{\"a\": [1, [...]], \"self\": {...}}\ntrue\ntrue\ntrue\nfalse\ntrue\nfalse\n"
+    );
+}
+
+#[test]
+fn test_compare_lists() {
+    let mut int = Interpreter::default();
+    int.eval_str("var xs = [1];\npush(xs, xs);").unwrap();
+    let mut error = |source| match int.eval_str(source).unwrap_err().remove(0) {
+        LoxError::RuntimeError(error) => error.error_type,
+        error => panic!("expected a runtime error, found {:?}", error),
+    };
+    let list_mismatch = RuntimeErrorType::TypeMismatch {
+        expected: "number",
+        actual: "list",
+    };
+    assert_eq!(error("xs < xs;"), list_mismatch);
+    assert_eq!(error("[1, 2] < [1, 3];"), list_mismatch);
+    assert_eq!(error("1 >= [1];"), list_mismatch);
+    assert_eq!(
+        error("nil <= 1;"),
+        RuntimeErrorType::TypeMismatch {
+            expected: "number",
+            actual: "nil"
+        }
+    );
+    assert_eq!(run("print \"a\" < \"b\";"), "true\n");
+}
+
+#[test]
+fn test_list_errors() {
+    let mut int = Interpreter::default();
+    int.eval_str("var xs = [1, 2, 3];").unwrap();
+    let mut error = |source| match int.eval_str(source).unwrap_err().remove(0) {
+        LoxError::RuntimeError(error) => error.error_type,
+        error => panic!("expected a runtime error, found {:?}", error),
+    };
+    assert_eq!(
+        error("xs[-1];"),
+        RuntimeErrorType::NegativeIndex { index: -1.0 }
+    );
+    assert_eq!(
+        error("xs[3] = 0;"),
+        RuntimeErrorType::IndexOutOfRange { index: 3, len: 3 }
+    );
+    assert_eq!(
+        error("xs[0.5];"),
+        RuntimeErrorType::FractionalIndex { index: 0.5 }
+    );
+    assert_eq!(
+        error("xs[\"0\"];"),
+        RuntimeErrorType::TypeMismatch {
+            expected: "number",
+            actual: "string"
+        }
+    );
+    assert_eq!(
+        error("1[0];"),
+        RuntimeErrorType::TypeMismatch {
+            expected: "list or map",
+            actual: "number"
+        }
+    );
+    assert_eq!(
+        error("slice(xs, 0, 4);"),
+        RuntimeErrorType::IndexOutOfRange { index: 4, len: 3 }
+    );
+    assert_eq!(
+        error("pop([]);"),
+        RuntimeErrorType::Native {
+            message: "Cannot pop from an empty list".into()
+        }
+    );
+}
+
+#[test]
+fn test_interpolation() {
+    let output = run(r#"
+        var name = "Ada";
+        var count = 2;
+        print "Hello ${name}, you have ${count + 1} items";
+        print "${[1, "two"]} ${nil}${true} ${"nested ${count}"}";
+        print "$${{"a": 1}["a"]}";
+    "#);
+    assert_eq!(
+        output,
+        "Hello Ada, you have 3 items\n[1, \"two\"] niltrue nested 2\n$1\n"
+    );
+}
+
+#[test]
+fn test_string_syntax() {
+    let output = run(r#"
+        var n = 1;
+        print "tab\there, \"quoted\" \\ \${n} = ${n}\u{21}";
+        print r"raw \n ${n}";
+        print "two
+lines ${n + 1}";
+    "#);
+    assert_eq!(
+        output,
+        "tab\there, \"quoted\" \\ ${n} = 1!\nraw \\n ${n}\ntwo\nlines 2\n"
+    );
+}
+
+#[test]
+fn test_maps() {
+    let output = run(r#"
+        var m = {"b": 2, "a": 1, 3: [nil], true: "yes"};
+        print m;
+        print m["a"] + len(m[3]);
+        var alias = m;
+        alias["c"] = 3;
+        print len(m);
+        print keys(m);
+        print values({"x": 1, "y": 2});
+        print has(m, "c") and !has(m, "d");
+        print remove(m, "c");
+        print remove(m, "c");
+        var nan = 0 / 0;
+        m[nan] = "nan";
+        print m[0 / 0];
+        m[-0] = "zero";
+        print m[0];
+        var ks = keys({"x": 1, "y": 2});
+        var i = 0;
+        while (i < len(ks)) {
+            print ks[i];
+            i = i + 1;
+        }
+    "#);
+    assert_eq!(
+        output,
+        "{true: \"yes\", 3: [nil], \"a\": 1, \"b\": 2}\n2\n5\n[true, 3, \"a\", \"b\", \"c\"]\n\
+         [1, 2]\ntrue\n3\nnil\nnan\nzero\nx\ny\n"
+    );
+}
+
+#[test]
+fn test_map_errors() {
+    let mut int = Interpreter::default();
+    int.eval_str("var m = {\"a\": 1};").unwrap();
+    let mut error = |source| match int.eval_str(source).unwrap_err().remove(0) {
+        LoxError::RuntimeError(error) => error.error_type,
+        error => panic!("expected a runtime error, found {:?}", error),
+    };
+    assert_eq!(
+        error("m[\"b\"];"),
+        RuntimeErrorType::UndefinedKey {
+            key: "\"b\"".into()
+        }
+    );
+    assert_eq!(
+        error("m[[]] = 1;"),
+        RuntimeErrorType::InvalidKey { actual: "list" }
+    );
+    assert_eq!(
+        error("{m: 1};"),
+        RuntimeErrorType::InvalidKey { actual: "map" }
+    );
+    assert_eq!(
+        error("has(m, clock);"),
+        RuntimeErrorType::InvalidKey { actual: "function" }
+    );
+}
+
+#[test]
+fn test_native_functions() {
+    let mut int = Interpreter::default();
+    assert!(matches!(int.eval_str("clock();"), Ok(Value::Number(_))));
+    assert_eq!(
+        int.eval_str("clock;").unwrap().to_string(),
+        "<native fn clock>"
+    );
+
+    int.define_native("sum", 2, |args| match args {
+        [Value::Number(a), Value::Number(b)] => Ok(Value::Number(a + b)),
+        _ => Err(RuntimeErrorType::Native {
+            message: "sum expects two numbers".into(),
+        }),
+    });
+    assert_eq!(
+        int.eval_str("var add = sum; add(1, 2);"),
+        Ok(Value::Number(3.0))
+    );
+
+    let mut error = |source| match int.eval_str(source).unwrap_err().remove(0) {
+        LoxError::RuntimeError(error) => error.error_type,
+        error => panic!("expected a runtime error, found {:?}", error),
+    };
+    assert_eq!(
+        error("sum(1);"),
+        RuntimeErrorType::ArityMismatch {
+            expected: 2,
+            found: 1
+        }
+    );
+    assert_eq!(
+        error("sum(1, nil);"),
+        RuntimeErrorType::Native {
+            message: "sum expects two numbers".into()
+        }
+    );
+}
+
+#[test]
+fn test_read_line() {
+    let output = run_with_input(
+        r#"
+        var line = readLine();
+        while (line != nil) {
+            print "> " + line;
+            line = readLine();
+        }
     "#,
-        &mut int,
+        "first\nsecond\r\nlast",
+    );
+    assert_eq!(output, "> first\n> second\n> last\n");
+}
+
+#[test]
+fn test_report_to_stderr() {
+    let stderr = Output::default();
+    let mut int = Interpreter::default()
+        .with_stdout(Output::default())
+        .with_stderr(stderr.clone());
+    let source = "print nil + 1;";
+    let errors = int.eval_str(source).unwrap_err();
+    int.report(&errors, source).unwrap();
+    assert_eq!(
+        stderr.contents(),
+        "error: Expected number or string, found nil\n --> 1:7\n  |\n1 | print nil + 1;\n  |       ^^^^^^^\n"
     );
-    assert_eq!(int.get_var("x"), &Value::Number(10.0));
 }
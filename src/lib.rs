@@ -0,0 +1,52 @@
+//! A Lox interpreter with a tree-walking and a bytecode backend.
+//!
+//! Most embedders only need [`Interpreter`]:
+//!
+//! ```
+//! let mut lox = rlox::Interpreter::default();
+//! lox.eval_str("var greeting = \"hello\";").unwrap();
+//! assert_eq!(lox.eval_str("greeting + \" world\";").unwrap().to_string(), "hello world");
+//! ```
+
+// Lets code generated by `rlox_macros` name this crate from inside it.
+extern crate self as rlox;
+
+pub mod ast_print;
+pub mod bytecode;
+pub mod class;
+pub mod convert;
+pub mod diagnostic;
+mod environment;
+pub mod error;
+pub mod expression;
+pub mod function;
+pub mod host;
+pub mod interpreter;
+pub mod native;
+pub mod operator;
+pub mod parser;
+pub mod resolver;
+pub mod scanner;
+pub mod span;
+pub mod statement;
+pub mod token;
+pub mod value;
+
+pub use error::{LoxError, Result};
+pub use interpreter::Interpreter;
+pub use parser::Parser;
+pub use rlox_macros::{lox_methods, LoxClass};
+pub use scanner::Scanner;
+pub use value::Value;
+
+macro_rules! impl_from_inner {
+    ($from:ident, $for:ty) => {
+        impl From<$from> for $for {
+            fn from(value: $from) -> Self {
+                Self::$from(value)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_from_inner;
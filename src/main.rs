@@ -1,79 +1,176 @@
-use std::io::{self, Write};
-
-use anyhow::Result;
-use colored::Colorize;
-
-use interpreter::Interpreter;
-use parser::Parser;
-use scanner::Scanner;
-
-#[cfg(test)]
-mod ast_print;
-mod environment;
-mod error;
-mod expression;
-mod interpreter;
-mod operator;
-mod parser;
-mod scanner;
-mod statement;
-mod token;
-mod value;
-
-fn main() -> Result<()> {
-    // clear screen
-    print!("\x1B[2J\x1B[1;1H");
-    let mut stdout = io::stdout();
-    let stdin = io::stdin();
-    let mut int = Interpreter::default();
-
-    // repl
-    let mut buf = String::new();
-    loop {
-        print!("{}", "rlox".bold().green());
-        print!("{}", " > ".purple());
-        stdout.flush()?;
-
-        buf.clear();
-        stdin.read_line(&mut buf)?;
-        if buf == "\n" {
-            continue;
+use std::{
+    env, fs,
+    io::{self, IsTerminal, Read},
+    process::ExitCode,
+};
+
+use rlox::{
+    bytecode::{disassemble, Compiler, Vm},
+    diagnostic::Diagnostic,
+    resolver::Resolver,
+    statement::{ExprStmt, PrintStmt, Stmt},
+    Interpreter, LoxError, Parser, Scanner, Value,
+};
+
+mod repl;
+
+const USAGE: &str = "Usage: rlox [--vm] [--dump-bytecode] [-e <code> | <script>]";
+
+/// Exit codes from sysexits.h, as used by clox.
+const EX_USAGE: u8 = 64;
+const EX_DATAERR: u8 = 65;
+const EX_NOINPUT: u8 = 66;
+const EX_SOFTWARE: u8 = 70;
+
+fn main() -> ExitCode {
+    let mut dump_bytecode = false;
+    let mut use_vm = false;
+    let mut input = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dump-bytecode" => dump_bytecode = true,
+            "--vm" => use_vm = true,
+            "-e" | "--eval" if input.is_none() => match args.next() {
+                Some(code) => input = Some(Input::Eval(code)),
+                None => return usage(),
+            },
+            path if input.is_none() && !path.starts_with('-') => {
+                input = Some(Input::File(path.into()))
+            }
+            _ => return usage(),
         }
+    }
+    let mut backend = if dump_bytecode || use_vm {
+        Backend::Vm(Vm::default())
+    } else {
+        Backend::TreeWalk(Interpreter::default())
+    };
 
-        let tokens = match Scanner::new(buf.clone()).scan_source() {
-            Ok(t) => t,
-            Err(errs) => {
-                for e in errs {
-                    eprintln!("{}", e.to_string().red());
-                }
-                continue;
+    let source = match input {
+        Some(Input::Eval(code)) => code,
+        Some(Input::File(path)) => match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("Could not read `{}`: {}", path, e);
+                return ExitCode::from(EX_NOINPUT);
             }
-        };
-        let stmts = match Parser::new(tokens).parse() {
-            Ok(s) => s,
-            Err(errs) => {
-                for e in errs {
-                    eprintln!("{}", e.to_string().red());
-                }
-                continue;
+        },
+        None if !io::stdin().is_terminal() => {
+            let mut source = String::new();
+            if let Err(e) = io::stdin().read_to_string(&mut source) {
+                eprintln!("Could not read stdin: {}", e);
+                return ExitCode::from(EX_NOINPUT);
             }
-        };
-        for stmt in &stmts {
-            if let Err(e) = int.interpret(stmt) {
-                eprintln!("{}", e.to_string().red());
+            source
+        }
+        None => {
+            return match repl::repl(&mut backend, dump_bytecode) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    ExitCode::FAILURE
+                }
             }
         }
+    };
+
+    match run(&mut backend, &source, dump_bytecode, false) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Compile) => ExitCode::from(EX_DATAERR),
+        Err(Failure::Runtime) => ExitCode::from(EX_SOFTWARE),
     }
 }
 
-macro_rules! impl_from_inner {
-    ($from:ident, $for:ty) => {
-        impl From<$from> for $for {
-            fn from(value: $from) -> Self {
-                Self::$from(value)
+fn usage() -> ExitCode {
+    eprintln!("{}", USAGE);
+    ExitCode::from(EX_USAGE)
+}
+
+/// Runs `source` on `backend`, reporting any errors to stderr. With `echo`,
+/// the value of a final expression statement is printed.
+fn run(
+    backend: &mut Backend,
+    source: &str,
+    dump_bytecode: bool,
+    echo: bool,
+) -> Result<(), Failure> {
+    let result = match backend {
+        Backend::TreeWalk(int) => int.eval_str(source).map(|value| {
+            if echo {
+                println!("{}", value);
             }
-        }
+        }),
+        Backend::Vm(vm) => run_vm(vm, source, dump_bytecode, echo),
     };
+    result.map_err(|errors| {
+        for e in &errors {
+            report(e, source);
+        }
+        match errors.as_slice() {
+            [LoxError::RuntimeError(_)] => Failure::Runtime,
+            _ => Failure::Compile,
+        }
+    })
 }
 
-pub(crate) use impl_from_inner;
+fn run_vm(vm: &mut Vm, source: &str, dump_bytecode: bool, echo: bool) -> Result<(), Vec<LoxError>> {
+    let tokens = Scanner::new(source.into()).scan_source()?;
+    let mut stmts = Parser::new(tokens).parse()?;
+    // The VM has no result to hand back, so echoing compiles to a print.
+    if echo {
+        match stmts.pop() {
+            Some(Stmt::ExprStmt(ExprStmt { expr, span })) => {
+                stmts.push(PrintStmt { expr, span }.into())
+            }
+            Some(stmt) => stmts.push(stmt),
+            None => {}
+        }
+    }
+    Resolver::default().resolve(&stmts)?;
+    let script = Compiler::default().compile(&stmts)?;
+    if dump_bytecode {
+        print!("{}", disassemble(&script));
+    }
+    vm.interpret(script).map_err(|e| vec![e])
+}
+
+/// Prints `error` as a diagnostic pointing into `source`, colored only when
+/// stderr is a terminal.
+fn report(error: impl Into<Diagnostic>, source: &str) {
+    let color = io::stderr().is_terminal();
+    eprint!("{}", error.into().render(source, color));
+}
+
+enum Input {
+    Eval(String),
+    File(String),
+}
+
+/// Which stage a program failed in, which decides the exit code.
+enum Failure {
+    Compile,
+    Runtime,
+}
+
+enum Backend {
+    TreeWalk(Interpreter),
+    Vm(Vm),
+}
+
+impl Backend {
+    fn globals(&self) -> Vec<(String, Value)> {
+        match self {
+            Backend::TreeWalk(int) => int.globals(),
+            Backend::Vm(vm) => vm.globals(),
+        }
+    }
+
+    /// Starts over with a fresh backend of the same kind.
+    fn reset(&mut self) {
+        *self = match self {
+            Backend::TreeWalk(_) => Backend::TreeWalk(Interpreter::default()),
+            Backend::Vm(_) => Backend::Vm(Vm::default()),
+        };
+    }
+}
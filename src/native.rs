@@ -0,0 +1,167 @@
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::BTreeMap,
+    fmt::Debug,
+    io::{self, BufRead},
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    convert::{FromArgs, FromLox, IntoLox},
+    error::RuntimeErrorType,
+    value::{whole_index, MapKey, Value},
+};
+
+type List = Rc<RefCell<Vec<Value>>>;
+type Map = Rc<RefCell<BTreeMap<MapKey, Value>>>;
+
+/// Where `readLine()` reads from, shared between a backend and its native.
+/// `None` reads the process's stdin without holding its lock between calls.
+pub(crate) type Input = Rc<RefCell<Option<Box<dyn BufRead>>>>;
+
+/// The Rust side of a native function. Arguments have already been checked
+/// against the declared arity.
+pub type NativeFn = dyn Fn(&[Value]) -> Result<Value, RuntimeErrorType>;
+
+/// A function implemented in Rust and callable from Lox.
+pub struct NativeFunction {
+    pub name: String,
+    pub arity: usize,
+    function: Box<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new(
+        name: impl Into<String>,
+        arity: usize,
+        function: impl Fn(&[Value]) -> Result<Value, RuntimeErrorType> + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            arity,
+            function: Box::new(function),
+        }
+    }
+
+    /// Wraps a function over Rust types, converting its arguments from and
+    /// its result into Lox values.
+    pub fn from_fn<Args, R>(
+        name: impl Into<String>,
+        function: impl Fn(Args) -> Result<R, RuntimeErrorType> + 'static,
+    ) -> Self
+    where
+        Args: FromArgs,
+        R: IntoLox,
+    {
+        Self::new(name, Args::ARITY, move |args| {
+            function(Args::from_args(args)?).map(R::into_lox)
+        })
+    }
+
+    pub fn call(&self, arguments: &[Value]) -> Result<Value, RuntimeErrorType> {
+        (self.function)(arguments)
+    }
+}
+
+/// The natives every interpreter starts out with.
+pub fn defaults() -> Vec<NativeFunction> {
+    vec![
+        NativeFunction::new("clock", 0, |_| {
+            let elapsed = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            Ok(Value::Number(elapsed.as_secs_f64()))
+        }),
+        NativeFunction::from_fn("len", |(value,): (Value,)| match value {
+            Value::List(list) => Ok(list.borrow().len()),
+            Value::Map(map) => Ok(map.borrow().len()),
+            Value::String(s) => Ok(s.chars().count()),
+            value => Err(RuntimeErrorType::TypeMismatch {
+                expected: "list, map or string",
+                actual: value.type_name(),
+            }),
+        }),
+        NativeFunction::from_fn("push", |(list, value): (List, Value)| {
+            list.borrow_mut().push(value);
+            Ok(())
+        }),
+        NativeFunction::from_fn("pop", |(list,): (List,)| {
+            list.borrow_mut()
+                .pop()
+                .ok_or_else(|| RuntimeErrorType::Native {
+                    message: "Cannot pop from an empty list".into(),
+                })
+        }),
+        NativeFunction::new("slice", 3, |args| slice(&args[0], &args[1], &args[2])),
+        NativeFunction::from_fn("keys", |(map,): (Map,)| {
+            Ok(Value::list(
+                map.borrow().keys().cloned().map(Value::from).collect(),
+            ))
+        }),
+        NativeFunction::from_fn("values", |(map,): (Map,)| {
+            Ok(Value::list(map.borrow().values().cloned().collect()))
+        }),
+        NativeFunction::from_fn("has", |(map, key): (Map, Value)| {
+            Ok(map.borrow().contains_key(&MapKey::try_from(&key)?))
+        }),
+        // Returns the removed value, or `nil` if there was none.
+        NativeFunction::from_fn("remove", |(map, key): (Map, Value)| {
+            Ok(map.borrow_mut().remove(&MapKey::try_from(&key)?))
+        }),
+    ]
+}
+
+/// `readLine()`, returning the next line of `input` or `nil` at its end.
+pub(crate) fn read_line(input: Input) -> NativeFunction {
+    NativeFunction::new("readLine", 0, move |_| {
+        let mut line = String::new();
+        let read = match input.borrow_mut().as_mut() {
+            Some(input) => input.read_line(&mut line)?,
+            None => io::stdin().read_line(&mut line)?,
+        };
+        if read == 0 {
+            return Ok(Value::Nil);
+        }
+        let len = line.trim_end_matches(['\n', '\r']).len();
+        line.truncate(len);
+        Ok(Value::String(line))
+    })
+}
+
+/// Copies the elements of `list` from `start` up to but not including `end`.
+fn slice(list: &Value, start: &Value, end: &Value) -> Result<Value, RuntimeErrorType> {
+    let list = List::from_lox(list)?;
+    let list = list.borrow();
+    let (start, end) = (whole_index(start)?, whole_index(end)?);
+    if end > list.len() {
+        return Err(RuntimeErrorType::IndexOutOfRange {
+            index: end,
+            len: list.len(),
+        });
+    }
+    Ok(Value::list(list[start.min(end)..end].to_vec()))
+}
+
+impl Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NativeFunction")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish_non_exhaustive()
+    }
+}
+
+// Natives compare by identity, like other functions.
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl PartialOrd for NativeFunction {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self == other).then_some(Ordering::Equal)
+    }
+}
@@ -1,6 +1,5 @@
 use std::sync::LazyLock;
 
-use anyhow::bail;
 use rustc_hash::FxHashMap;
 
 use crate::token::{Token, TokenType};
@@ -49,18 +48,20 @@ impl From<BinaryOperator> for Operator {
 }
 
 impl<'a> TryFrom<&'a Token> for UnaryOperator {
-    type Error = anyhow::Error;
+    /// The token that is not an operator.
+    type Error = TokenType;
     fn try_from(value: &'a Token) -> Result<Self, Self::Error> {
         match value.token_type {
             TokenType::Minus => Ok(Self::Minus),
             TokenType::Bang => Ok(Self::Not),
-            _ => bail!("{:?} is not a unary operator", value),
+            _ => Err(value.token_type.clone()),
         }
     }
 }
 
 impl<'a> TryFrom<&'a Token> for BinaryOperator {
-    type Error = anyhow::Error;
+    /// The token that is not an operator.
+    type Error = TokenType;
     fn try_from(value: &'a Token) -> Result<Self, Self::Error> {
         match value.token_type {
             TokenType::Minus => Ok(Self::Minus),
@@ -76,7 +77,7 @@ impl<'a> TryFrom<&'a Token> for BinaryOperator {
             TokenType::LessEqual => Ok(Self::LessEqual),
             TokenType::And => Ok(Self::And),
             TokenType::Or => Ok(Self::Or),
-            _ => bail!("{:?} is not a binary operator", value),
+            _ => Err(value.token_type.clone()),
         }
     }
 }
@@ -1,18 +1,26 @@
-use std::iter::Peekable;
-
-use anyhow::{anyhow, bail, Error, Result};
+use std::{cell::Cell, iter::Peekable, rc::Rc};
 
 use crate::{
-    error::{ParseError, ParseErrorType},
-    expression::{Assign, Binary, Expression, Grouping, Literal, Unary, Var},
+    error::{LoxError, ParseError, ParseErrorType, Result},
+    expression::{
+        Assign, Binary, Call, Expression, Get, Grouping, Index, Interpolation, List, Literal,
+        LiteralValue, Map, Set, Super, This, Unary, Var,
+    },
     operator::BinaryOperator,
-    statement::{BlockStmt, IfStmt, Stmt, VarDecl, WhileStmt},
+    span::Span,
+    statement::{
+        BlockStmt, BreakStmt, ClassDecl, ContinueStmt, ExprStmt, FunDecl, IfStmt, PrintStmt,
+        ReturnStmt, Stmt, VarDecl, WhileStmt,
+    },
     token::{Token, TokenType, Tokens},
 };
 
 pub struct Parser {
     tokens: Peekable<Tokens>,
-    errors: Vec<Error>,
+    errors: Vec<LoxError>,
+    /// Labels of the loops enclosing the current statement, within the
+    /// current function.
+    labels: Vec<String>,
 }
 
 macro_rules! parse_error {
@@ -25,7 +33,7 @@ macro_rules! parse_error {
                     expected,
                     found: found.token_type,
                 },
-                line_num: found.line_num,
+                span: found.span,
             }
             .into())
         }
@@ -35,12 +43,15 @@ macro_rules! parse_error {
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
         let tokens = Tokens::from(tokens).peekable();
-        let errors = Vec::new();
-        Self { tokens, errors }
+        Self {
+            tokens,
+            errors: Vec::new(),
+            labels: Vec::new(),
+        }
     }
 
-    pub fn parse(mut self) -> Result<Vec<Stmt>, Vec<Error>> {
-        let parsed = self.parse_until(TokenType::EOF);
+    pub fn parse(mut self) -> Result<Vec<Stmt>, Vec<LoxError>> {
+        let (parsed, _) = self.parse_until(TokenType::EOF);
         if self.errors.is_empty() {
             Ok(parsed)
         } else {
@@ -48,7 +59,9 @@ impl Parser {
         }
     }
 
-    fn parse_until(&mut self, until: TokenType) -> Vec<Stmt> {
+    /// Parses declarations up to and including `until`, returning them along
+    /// with the span of the closing token.
+    fn parse_until(&mut self, until: TokenType) -> (Vec<Stmt>, Span) {
         let mut stmts = Vec::new();
         while self.tokens.peek().is_some_and(|t| t.token_type != until) {
             match self.parse_decl() {
@@ -59,50 +72,169 @@ impl Parser {
                 }
             }
         }
-        self.tokens.next();
+        let end = self.tokens.next().map(|t| t.span).unwrap_or_default();
+
+        (stmts, end)
+    }
+
+    #[inline]
+    fn peek_span(&mut self) -> Span {
+        self.tokens.peek().map(|t| t.span).unwrap_or_default()
+    }
 
-        stmts
+    /// The next token, or an `EOF` token once the input is exhausted.
+    #[inline]
+    fn peek_token(&mut self) -> Token {
+        self.tokens
+            .peek()
+            .cloned()
+            .unwrap_or_else(|| Token::new(TokenType::EOF, Span::default()))
     }
 
     #[inline]
-    fn expect_token(&mut self, token_type: TokenType) -> Result<()> {
+    fn expect_token(&mut self, token_type: TokenType) -> Result<Token> {
         match self.tokens.next_if(|t| t.token_type == token_type) {
-            Some(_) => Ok(()),
-            None => parse_error!(expect token_type, found self.tokens.peek().cloned().unwrap()),
+            Some(t) => Ok(t),
+            None => parse_error!(expect token_type, found self.peek_token()),
+        }
+    }
+
+    #[inline]
+    fn expect_identifier(&mut self) -> Result<(String, Span)> {
+        match self
+            .tokens
+            .next_if(|t| matches!(t.token_type, TokenType::Identifier(_)))
+        {
+            Some(Token {
+                token_type: TokenType::Identifier(name),
+                span,
+            }) => Ok((name, span)),
+            _ => {
+                let found = self.peek_token();
+                Err(ParseError {
+                    error_type: ParseErrorType::ExpectedIdentifier {
+                        found: found.token_type,
+                    },
+                    span: found.span,
+                }
+                .into())
+            }
         }
     }
 
     fn parse_decl(&mut self) -> Result<Stmt> {
-        let stmt = if self.expect_token(TokenType::Var).is_ok() {
-            let Expression::Var(Var { name }) = self.parse_primary()? else {
-                bail!("Expected variable name")
-            };
+        let stmt = if let Ok(fun) = self.expect_token(TokenType::Fun) {
+            self.parse_function(fun.span)?.into()
+        } else if let Ok(class) = self.expect_token(TokenType::Class) {
+            self.parse_class_decl(class.span)?
+        } else if let Ok(var) = self.expect_token(TokenType::Var) {
+            let (name, _) = self.expect_identifier()?;
 
             let mut initializer = None;
             if self.expect_token(TokenType::Equal).is_ok() {
                 let expr = self.parse_expr()?;
                 initializer = Some(Box::new(expr));
             }
-            self.expect_token(TokenType::Semicolon)?;
+            let semicolon = self.expect_token(TokenType::Semicolon)?;
 
-            VarDecl { name, initializer }.into()
+            VarDecl {
+                name,
+                initializer,
+                span: var.span.to(semicolon.span),
+            }
+            .into()
         } else {
             self.parse_stmt()?
         };
         Ok(stmt)
     }
 
+    fn parse_function(&mut self, start: Span) -> Result<FunDecl> {
+        let (name, _) = self.expect_identifier()?;
+
+        self.expect_token(TokenType::LeftParen)?;
+        let mut params = Vec::new();
+        if self.expect_token(TokenType::RightParen).is_err() {
+            loop {
+                params.push(self.expect_identifier()?.0);
+                if self.expect_token(TokenType::Comma).is_err() {
+                    break;
+                }
+            }
+            self.expect_token(TokenType::RightParen)?;
+        }
+
+        self.expect_token(TokenType::LeftBrace)?;
+        let labels = std::mem::take(&mut self.labels);
+        let (body, end) = self.parse_until(TokenType::RightBrace);
+        self.labels = labels;
+
+        Ok(FunDecl {
+            name,
+            params,
+            body,
+            span: start.to(end),
+        })
+    }
+
+    fn parse_class_decl(&mut self, start: Span) -> Result<Stmt> {
+        let (name, _) = self.expect_identifier()?;
+        let superclass = match self.expect_token(TokenType::Less) {
+            Ok(_) => {
+                let (name, span) = self.expect_identifier()?;
+                Some(Var::new(name, span))
+            }
+            Err(_) => None,
+        };
+
+        self.expect_token(TokenType::LeftBrace)?;
+        let mut methods = Vec::new();
+        let end = loop {
+            if let Ok(brace) = self.expect_token(TokenType::RightBrace) {
+                break brace.span;
+            }
+            let start = self.peek_span();
+            methods.push(self.parse_function(start).map(Rc::new)?);
+        };
+
+        Ok(ClassDecl {
+            name,
+            superclass,
+            methods,
+            span: start.to(end),
+        }
+        .into())
+    }
+
     fn parse_stmt(&mut self) -> Result<Stmt> {
         let stmt = match self.tokens.peek().map(|t| &t.token_type) {
             Some(TokenType::Print) => self.parse_print_stmt()?,
             Some(TokenType::LeftBrace) => self.parse_block_stmt()?,
             Some(TokenType::If) => self.parse_if_stmt()?,
-            Some(TokenType::While) => self.parse_while_stmt()?,
-            Some(TokenType::For) => self.parse_for_stmt()?,
+            Some(TokenType::While) => self.parse_while_stmt(None)?,
+            Some(TokenType::For) => self.parse_for_stmt(None)?,
+            Some(TokenType::Return) => self.parse_return_stmt()?,
+            Some(TokenType::Break) => {
+                let (label, span) = self.parse_loop_control()?;
+                BreakStmt { label, span }.into()
+            }
+            Some(TokenType::Continue) => {
+                let (label, span) = self.parse_loop_control()?;
+                ContinueStmt { label, span }.into()
+            }
             _ => {
-                let stmt = Stmt::ExprStmt(self.parse_expr()?);
-                self.expect_token(TokenType::Semicolon)?;
-                stmt
+                let expr = self.parse_expr()?;
+                if let Expression::Var(label) = &expr {
+                    if self.expect_token(TokenType::Colon).is_ok() {
+                        return self.parse_labeled_loop(label);
+                    }
+                }
+                let semicolon = self.expect_token(TokenType::Semicolon)?;
+                ExprStmt {
+                    span: expr.span().to(semicolon.span),
+                    expr,
+                }
+                .into()
             }
         };
 
@@ -110,67 +242,176 @@ impl Parser {
     }
 
     fn parse_print_stmt(&mut self) -> Result<Stmt> {
-        self.tokens.next();
-        let stmt = Stmt::PrintStmt(self.parse_expr()?);
-        self.expect_token(TokenType::Semicolon)?;
-        Ok(stmt)
+        let keyword = self.tokens.next().unwrap();
+        let expr = self.parse_expr()?;
+        let semicolon = self.expect_token(TokenType::Semicolon)?;
+        Ok(PrintStmt {
+            expr,
+            span: keyword.span.to(semicolon.span),
+        }
+        .into())
+    }
+
+    fn parse_return_stmt(&mut self) -> Result<Stmt> {
+        let keyword = self.tokens.next().unwrap();
+        let value = match self.tokens.peek().map(|t| &t.token_type) {
+            Some(TokenType::Semicolon) => None,
+            _ => Some(Box::new(self.parse_expr()?)),
+        };
+        let semicolon = self.expect_token(TokenType::Semicolon)?;
+
+        Ok(ReturnStmt {
+            value,
+            span: keyword.span.to(semicolon.span),
+        }
+        .into())
+    }
+
+    /// Parses `break` or `continue` with its optional label, returning the
+    /// label and the span of the statement.
+    fn parse_loop_control(&mut self) -> Result<(Option<String>, Span)> {
+        let keyword = self.tokens.next().unwrap();
+        let label = match self.expect_identifier() {
+            Ok((name, _)) if self.labels.contains(&name) => Some(name),
+            Ok((name, span)) => {
+                return Err(ParseError {
+                    error_type: ParseErrorType::UndefinedLabel { name },
+                    span,
+                }
+                .into())
+            }
+            Err(_) => None,
+        };
+        let semicolon = self.expect_token(TokenType::Semicolon)?;
+        Ok((label, keyword.span.to(semicolon.span)))
+    }
+
+    /// Parses the loop following `label:`.
+    fn parse_labeled_loop(&mut self, label: &Var) -> Result<Stmt> {
+        if self.labels.contains(&label.name) {
+            return Err(ParseError {
+                error_type: ParseErrorType::ShadowedLabel {
+                    name: label.name.clone(),
+                },
+                span: label.span,
+            }
+            .into());
+        }
+        let name = Some(label.name.clone());
+        match self.peek_token().token_type {
+            TokenType::While => self.parse_while_stmt(name),
+            TokenType::For => self.parse_for_stmt(name),
+            _ => parse_error!(expect TokenType::While, found self.peek_token()),
+        }
+    }
+
+    /// Parses the body of a loop, with its label in scope.
+    fn parse_loop_body(&mut self, label: &Option<String>) -> Result<Stmt> {
+        self.labels.extend(label.clone());
+        let body = self.parse_stmt();
+        if label.is_some() {
+            self.labels.pop();
+        }
+        body
     }
 
+    /// Parses a block, or an expression statement starting with a map
+    /// literal. It is a map if the first expression inside the braces is
+    /// followed by a `:`, unless that expression is the label of a loop.
     fn parse_block_stmt(&mut self) -> Result<Stmt> {
-        self.tokens.next();
-        let stmts = self.parse_until(TokenType::RightBrace);
+        let brace = self.tokens.next().unwrap();
+        let starts_with_expr = !matches!(
+            self.peek_token().token_type,
+            TokenType::LeftBrace | TokenType::RightBrace | TokenType::EOF
+        ) && !self.is_stmt_start();
+        let mut stmts = Vec::new();
+        if starts_with_expr {
+            let expr = self.parse_expr()?;
+            let stmt = match (&expr, self.expect_token(TokenType::Colon)) {
+                (Expression::Var(label), Ok(_))
+                    if matches!(
+                        self.peek_token().token_type,
+                        TokenType::While | TokenType::For
+                    ) =>
+                {
+                    self.parse_labeled_loop(label)?
+                }
+                (_, Ok(_)) => {
+                    let map = self.finish_map(brace.span, expr)?;
+                    let expr = self.parse_postfix(map)?;
+                    let expr = self.parse_infix(expr, 0)?;
+                    let semicolon = self.expect_token(TokenType::Semicolon)?;
+                    return Ok(ExprStmt {
+                        span: expr.span().to(semicolon.span),
+                        expr,
+                    }
+                    .into());
+                }
+                (_, Err(_)) => {
+                    let semicolon = self.expect_token(TokenType::Semicolon)?;
+                    ExprStmt {
+                        span: expr.span().to(semicolon.span),
+                        expr,
+                    }
+                    .into()
+                }
+            };
+            stmts.push(stmt);
+        }
+        let (rest, end) = self.parse_until(TokenType::RightBrace);
+        stmts.extend(rest);
 
-        Ok(BlockStmt { stmts }.into())
+        Ok(BlockStmt {
+            stmts,
+            span: brace.span.to(end),
+        }
+        .into())
     }
 
     fn parse_if_stmt(&mut self) -> Result<Stmt> {
-        self.tokens.next();
-        let next = self.tokens.peek();
-        if next
-            .filter(|t| t.token_type == TokenType::LeftParen)
-            .is_none()
-        {
-            parse_error!(expect TokenType::LeftParen, found next.unwrap().clone());
-        }
-        let Expression::Grouping(grouping) = self.parse_expr()? else {
-            bail!("Failed to parse")
-        };
-        let condition = grouping.expr;
+        let keyword = self.tokens.next().unwrap();
+        let condition = self.parse_condition()?;
         let then_branch = self.parse_stmt().map(Box::new)?;
         let else_branch = match self.expect_token(TokenType::Else) {
             Ok(_) => self.parse_stmt().map(Box::new).map(Some)?,
             Err(_) => None,
         };
+        let end = else_branch.as_ref().unwrap_or(&then_branch).span();
 
         Ok(IfStmt {
             condition,
             then_branch,
             else_branch,
+            span: keyword.span.to(end),
         }
         .into())
     }
 
-    fn parse_while_stmt(&mut self) -> Result<Stmt> {
-        self.tokens.next();
+    fn parse_while_stmt(&mut self, label: Option<String>) -> Result<Stmt> {
+        let keyword = self.tokens.next().unwrap();
+        let condition = self.parse_condition()?;
+        let body = self.parse_loop_body(&label).map(Box::new)?;
 
-        let next = self.tokens.peek();
-        if next
-            .filter(|t| t.token_type == TokenType::LeftParen)
-            .is_none()
-        {
-            parse_error!(expect TokenType::LeftParen, found next.unwrap().clone());
+        Ok(WhileStmt {
+            condition,
+            span: keyword.span.to(body.span()),
+            body,
+            increment: None,
+            label,
         }
-        let Expression::Grouping(grouping) = self.parse_expr()? else {
-            bail!("Failed to parse")
-        };
-        let condition = grouping.expr;
-        let body = self.parse_stmt().map(Box::new)?;
+        .into())
+    }
 
-        Ok(WhileStmt { condition, body }.into())
+    /// Parses the parenthesized condition of an `if` or `while`.
+    fn parse_condition(&mut self) -> Result<Box<Expression>> {
+        self.expect_token(TokenType::LeftParen)?;
+        let condition = self.parse_expr()?;
+        self.expect_token(TokenType::RightParen)?;
+        Ok(Box::new(condition))
     }
 
-    fn parse_for_stmt(&mut self) -> Result<Stmt> {
-        self.tokens.next();
+    fn parse_for_stmt(&mut self, label: Option<String>) -> Result<Stmt> {
+        let keyword = self.tokens.next().unwrap();
         self.expect_token(TokenType::LeftParen)?;
 
         let mut stmts = Vec::new();
@@ -189,7 +430,11 @@ impl Parser {
         }
 
         let condition = match self.tokens.peek().map(|t| &t.token_type) {
-            Some(TokenType::Semicolon) => Literal::True.into(),
+            Some(TokenType::Semicolon) => Literal {
+                value: LiteralValue::True,
+                span: self.peek_span(),
+            }
+            .into(),
             Some(_) => self.parse_expr()?,
             None => panic!("Token stream ended unexpectedly"),
         };
@@ -202,23 +447,20 @@ impl Parser {
         };
         self.expect_token(TokenType::RightParen)?;
 
-        let mut body = self.parse_stmt()?;
-        if let Some(e) = increment {
-            body = BlockStmt {
-                stmts: vec![body, Stmt::ExprStmt(e)],
-            }
-            .into()
-        }
-
+        let body = self.parse_loop_body(&label)?;
+        let span = keyword.span.to(body.span());
         stmts.push(
             WhileStmt {
                 condition: Box::new(condition),
                 body: Box::new(body),
+                increment: increment.map(Box::new),
+                label,
+                span,
             }
             .into(),
         );
 
-        Ok(BlockStmt { stmts }.into())
+        Ok(BlockStmt { stmts, span }.into())
     }
 
     fn parse_expr(&mut self) -> Result<Expression> {
@@ -226,7 +468,13 @@ impl Parser {
     }
 
     fn parse_expr_bp(&mut self, right_bp: u8) -> Result<Expression> {
-        let mut expr = self.parse_head()?;
+        let head = self.parse_head()?;
+        self.parse_infix(head, right_bp)
+    }
+
+    /// Parses the binary operators following `expr` that bind tighter than
+    /// `right_bp`.
+    fn parse_infix(&mut self, mut expr: Expression, right_bp: u8) -> Result<Expression> {
         while let Some(op) = self
             .tokens
             .peek()
@@ -247,28 +495,118 @@ impl Parser {
         {
             let operator = t.try_into().unwrap();
             let expr = self.parse_expr_bp(PREFIX_BINDING_POWER).map(Box::new)?;
-            let unary = Unary { operator, expr }.into();
+            let span = t.span.to(expr.span());
+            let unary = Unary {
+                operator,
+                expr,
+                span,
+            }
+            .into();
 
             Ok(unary)
         } else {
-            self.parse_primary()
+            self.parse_call()
+        }
+    }
+
+    #[inline]
+    fn parse_call(&mut self) -> Result<Expression> {
+        let primary = self.parse_primary()?;
+        self.parse_postfix(primary)
+    }
+
+    /// Parses the calls, property accesses and subscripts following `expr`.
+    fn parse_postfix(&mut self, mut expr: Expression) -> Result<Expression> {
+        loop {
+            expr = match self.tokens.peek().map(|t| &t.token_type) {
+                Some(TokenType::LeftParen) => {
+                    self.tokens.next();
+                    self.finish_call(expr)?
+                }
+                Some(TokenType::Dot) => {
+                    self.tokens.next();
+                    let (name, name_span) = self.expect_identifier()?;
+                    Get {
+                        span: expr.span().to(name_span),
+                        object: Box::new(expr),
+                        name,
+                    }
+                    .into()
+                }
+                Some(TokenType::LeftBracket) => {
+                    self.tokens.next();
+                    let index = self.parse_expr()?;
+                    let bracket = self.expect_token(TokenType::RightBracket)?;
+                    Index {
+                        span: expr.span().to(bracket.span),
+                        object: Box::new(expr),
+                        index: Box::new(index),
+                    }
+                    .into()
+                }
+                _ => break,
+            };
         }
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expression) -> Result<Expression> {
+        let mut arguments = Vec::new();
+        if self
+            .tokens
+            .peek()
+            .is_some_and(|t| t.token_type != TokenType::RightParen)
+        {
+            loop {
+                arguments.push(self.parse_expr()?);
+                if self.expect_token(TokenType::Comma).is_err() {
+                    break;
+                }
+            }
+        }
+        let paren = self.expect_token(TokenType::RightParen)?;
+
+        Ok(Call {
+            span: callee.span().to(paren.span),
+            callee: Box::new(callee),
+            arguments,
+        }
+        .into())
     }
 
     #[inline]
     fn parse_tail(&mut self, left: Expression, op: BinaryOperator) -> Result<Expression> {
         let operator_bp = infix_binding_power(&op);
         let right = self.parse_expr_bp(operator_bp)?;
+        let span = left.span().to(right.span());
         let expr = match op {
-            BinaryOperator::Equal => Assign {
-                name: Box::new(left),
-                value: Box::new(right),
-            }
-            .into(),
+            BinaryOperator::Equal => match left {
+                Expression::Get(Get { object, name, .. }) => Set {
+                    object,
+                    name,
+                    value: Box::new(right),
+                    span,
+                }
+                .into(),
+                left @ (Expression::Var(_) | Expression::Index(_)) => Assign {
+                    target: Box::new(left),
+                    value: Box::new(right),
+                    span,
+                }
+                .into(),
+                left => {
+                    return Err(ParseError {
+                        error_type: ParseErrorType::InvalidAssignmentTarget,
+                        span: left.span(),
+                    }
+                    .into())
+                }
+            },
             _ => Binary {
                 operator: op,
                 left: Box::new(left),
                 right: Box::new(right),
+                span,
             }
             .into(),
         };
@@ -278,32 +616,161 @@ impl Parser {
 
     #[inline]
     fn parse_primary(&mut self) -> Result<Expression> {
-        let t = self
-            .tokens
-            .next()
-            .ok_or_else(|| anyhow!("Expected token"))?;
+        let t = self.peek_token();
+        self.tokens.next();
 
+        let literal = |value| Literal {
+            value,
+            span: t.span,
+        };
         let expr = match t.token_type {
-            TokenType::True => Literal::True.into(),
-            TokenType::False => Literal::False.into(),
-            TokenType::Nil => Literal::Nil.into(),
-            TokenType::Number(n) => Literal::Number(n).into(),
-            TokenType::String(s) => Literal::String(s).into(),
-            TokenType::Identifier(name) => Var { name }.into(),
+            TokenType::True => literal(LiteralValue::True).into(),
+            TokenType::False => literal(LiteralValue::False).into(),
+            TokenType::Nil => literal(LiteralValue::Nil).into(),
+            TokenType::Number(n) => literal(LiteralValue::Number(n)).into(),
+            TokenType::String(ref s) => literal(LiteralValue::String(s.clone())).into(),
+            TokenType::Interpolation(_) => self.finish_interpolation(t)?,
+            TokenType::Identifier(name) => Var::new(name, t.span).into(),
+            TokenType::This => This {
+                span: t.span,
+                depth: Cell::default(),
+            }
+            .into(),
+            TokenType::Super => {
+                self.expect_token(TokenType::Dot)?;
+                let (method, method_span) = self.expect_identifier()?;
+                Super {
+                    method,
+                    span: t.span.to(method_span),
+                    depth: Cell::default(),
+                }
+                .into()
+            }
             TokenType::LeftParen => {
                 let expr = self.parse_expr()?;
-                self.expect_token(TokenType::RightParen)?;
+                let paren = self.expect_token(TokenType::RightParen)?;
 
                 Grouping {
                     expr: Box::new(expr),
+                    span: t.span.to(paren.span),
                 }
                 .into()
             }
-            _ => bail!("Unexpected token: `{}`", t.lexeme()),
+            TokenType::LeftBracket => self.finish_list(t.span)?,
+            TokenType::LeftBrace => match self.expect_token(TokenType::RightBrace) {
+                Ok(brace) => Map {
+                    entries: Vec::new(),
+                    span: t.span.to(brace.span),
+                }
+                .into(),
+                Err(_) => {
+                    let key = self.parse_expr()?;
+                    self.expect_token(TokenType::Colon)?;
+                    self.finish_map(t.span, key)?
+                }
+            },
+            found => {
+                return Err(ParseError {
+                    error_type: ParseErrorType::ExpectedExpression { found },
+                    span: t.span,
+                }
+                .into())
+            }
         };
         Ok(expr)
     }
 
+    /// Parses the rest of an interpolated string after its first segment.
+    /// Empty segments are left out.
+    fn finish_interpolation(&mut self, first: Token) -> Result<Expression> {
+        let start = first.span;
+        let mut parts = Vec::new();
+        let mut segment = first;
+        loop {
+            let (TokenType::Interpolation(s) | TokenType::String(s)) = &segment.token_type else {
+                unreachable!()
+            };
+            if !s.is_empty() {
+                parts.push(
+                    Literal {
+                        value: LiteralValue::String(s.clone()),
+                        span: segment.span,
+                    }
+                    .into(),
+                );
+            }
+            if let TokenType::String(_) = segment.token_type {
+                break;
+            }
+            if let Some(brace) = self
+                .tokens
+                .next_if(|t| t.token_type == TokenType::RightBrace)
+            {
+                return Err(ParseError {
+                    error_type: ParseErrorType::ExpectedExpression {
+                        found: TokenType::RightBrace,
+                    },
+                    span: interpolation_start(segment.span, brace.span),
+                }
+                .into());
+            }
+            parts.push(self.parse_expr()?);
+            self.expect_token(TokenType::RightBrace)?;
+            // The scanner resumes the string right after the `}`.
+            segment = self.tokens.next().expect("Interpolation should be closed");
+        }
+
+        Ok(Interpolation {
+            parts,
+            span: start.to(segment.span),
+        }
+        .into())
+    }
+
+    /// Parses the elements of a list literal, allowing a trailing comma.
+    fn finish_list(&mut self, start: Span) -> Result<Expression> {
+        let mut elements = Vec::new();
+        let end = loop {
+            if let Ok(bracket) = self.expect_token(TokenType::RightBracket) {
+                break bracket.span;
+            }
+            elements.push(self.parse_expr()?);
+            if self.expect_token(TokenType::Comma).is_err() {
+                break self.expect_token(TokenType::RightBracket)?.span;
+            }
+        };
+
+        Ok(List {
+            elements,
+            span: start.to(end),
+        }
+        .into())
+    }
+
+    /// Parses the rest of a map literal, whose first key and colon have been
+    /// consumed already. Allows a trailing comma.
+    fn finish_map(&mut self, start: Span, key: Expression) -> Result<Expression> {
+        let value = self.parse_expr()?;
+        let mut entries = vec![(key, value)];
+        let end = loop {
+            if self.expect_token(TokenType::Comma).is_err() {
+                break self.expect_token(TokenType::RightBrace)?.span;
+            }
+            if let Ok(brace) = self.expect_token(TokenType::RightBrace) {
+                break brace.span;
+            }
+            let key = self.parse_expr()?;
+            self.expect_token(TokenType::Colon)?;
+            entries.push((key, self.parse_expr()?));
+        };
+
+        Ok(Map {
+            entries,
+            span: start.to(end),
+        }
+        .into())
+    }
+
     fn synchronize(&mut self) {
         loop {
             match self.tokens.next().map(|t| t.token_type) {
@@ -326,11 +793,15 @@ impl Parser {
             matches!(
                 t.token_type,
                 TokenType::Var
+                    | TokenType::Fun
+                    | TokenType::Class
                     | TokenType::For
                     | TokenType::If
                     | TokenType::While
                     | TokenType::Print
                     | TokenType::Return
+                    | TokenType::Break
+                    | TokenType::Continue
             )
         })
     }
@@ -365,5 +836,18 @@ fn infix_binding_power(op: &BinaryOperator) -> u8 {
 
 const PREFIX_BINDING_POWER: u8 = BindingPower::Unary as u8;
 
+/// The span of the `${` ending `segment`, located from the `}` after it
+/// since a segment may span lines. Only whitespace lies between them, and if
+/// that includes a line break the `}` is used instead.
+fn interpolation_start(segment: Span, brace: Span) -> Span {
+    let start = segment.end - 2;
+    let distance = (brace.start - start) as u32;
+    if brace.column > distance {
+        Span::new(start, segment.end, brace.line, brace.column - distance)
+    } else {
+        brace
+    }
+}
+
 #[cfg(test)]
 mod tests;
@@ -108,7 +108,199 @@ snapshot_test!(
     }
 "#
 );
+snapshot_test!(
+    test_parse_fun_decl,
+    r#"
+    fun add(a, b) {
+        return a + b;
+    }
+    print add(1, 2);
+"#
+);
+snapshot_test!(
+    test_parse_class_decl,
+    r#"
+    class Point {
+        init(x) {
+            this.x = x;
+        }
+    }
+    Point(1).x;
+"#
+);
 
 should_panic!(test_missing_semicolon, "print 5 + 5");
 should_panic!(test_missing_var_name, "var 5 = 5;");
 should_panic!(test_missing_var_assign, "var x = ;");
+should_panic!(test_missing_fun_name, "fun (a) { return a; }");
+
+#[test]
+fn test_parse_lists() {
+    let mut printer = AstPrinter;
+    assert_eq!(printer.print(&parse_expr("[]")), "(list)");
+    assert_eq!(
+        printer.print(&parse_expr("[1, \"two\", [3],]")),
+        r#"(list 1 "two" (list 3))"#
+    );
+    assert_eq!(
+        printer.print(&parse_expr("xs[i + 1][0]")),
+        "([] ([] (var xs) (+ (var i) 1)) 0)"
+    );
+    assert_eq!(
+        printer.print(&parse_expr("f()[0] = xs[1]")),
+        "(= ([] (call (var f)) 0) ([] (var xs) 1))"
+    );
+}
+
+#[test]
+fn test_parse_maps() {
+    let mut printer = AstPrinter;
+    assert_eq!(printer.print(&parse_expr("{}")), "(map)");
+    assert_eq!(
+        printer.print(&parse_expr("{\"a\": 1, k: [2],}")),
+        r#"(map ("a" 1) ((var k) (list 2)))"#
+    );
+
+    let print_stmts = |source| {
+        parse_stmts(source)
+            .iter()
+            .map(|stmt| AstPrinter.print_stmt(stmt))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        print_stmts("{\"a\": 1}[\"a\"] + 1;"),
+        [r#"(; (+ ([] (map ("a" 1)) "a") 1))"#]
+    );
+    assert_eq!(print_stmts("{}"), ["(block)"]);
+    assert_eq!(
+        print_stmts("{ x = 1; { print x; } }"),
+        ["(block (; (= (var x) 1)) (block (print (var x))))"]
+    );
+}
+
+#[test]
+fn test_parse_interpolation() {
+    let mut printer = AstPrinter;
+    assert_eq!(
+        printer.print(&parse_expr(
+            r#""Hello ${name}, you have ${count + 1} items""#
+        )),
+        r#"(interpolate "Hello " (var name) ", you have " (+ (var count) 1) " items")"#
+    );
+    assert_eq!(
+        printer.print(&parse_expr(r#""${"${x}"}""#)),
+        "(interpolate (interpolate (var x)))"
+    );
+
+    let parse_error = |source: &'static str| {
+        let tokens = Scanner::new(source.to_string()).scan_source().unwrap();
+        match Parser::new(tokens).parse().unwrap_err().remove(0) {
+            LoxError::ParseError(error) => error,
+            error => panic!("expected a parse error, found {:?}", error),
+        }
+    };
+    let empty = ParseError {
+        error_type: ParseErrorType::ExpectedExpression {
+            found: TokenType::RightBrace,
+        },
+        span: Span::new(8, 10, 1, 9),
+    };
+    assert_eq!(parse_error(r#"print "a${}b";"#), empty);
+    assert_eq!(parse_error(r#"print "a${ }";"#), empty);
+    assert_eq!(
+        parse_error("print \"\na${}\";").span,
+        Span::new(9, 11, 2, 2)
+    );
+}
+
+#[test]
+fn test_parse_loop_control() {
+    let stmts = parse_stmts("for (var i = 0; i < 3; i = i + 1) { if (i) continue; break; }");
+    assert_eq!(
+        AstPrinter.print_stmt(&stmts[0]),
+        "(block (var-decl i 0) (while (< (var i) 3) \
+         (block (if (var i) (continue)) (break)) (= (var i) (+ (var i) 1))))"
+    );
+
+    let stmts = parse_stmts("outer: while (a) { inner: for (;;) continue outer; break outer; }");
+    assert_eq!(
+        AstPrinter.print_stmt(&stmts[0]),
+        "(while outer: (var a) (block (block (while inner: true (continue outer))) (break outer)))"
+    );
+    let stmts = parse_stmts("{ outer: while (a) break outer; }");
+    assert_eq!(
+        AstPrinter.print_stmt(&stmts[0]),
+        "(block (while outer: (var a) (break outer)))"
+    );
+}
+
+#[test]
+fn test_parse_error_types() {
+    let parse_error = |source: &'static str| {
+        let tokens = Scanner::new(source.to_string()).scan_source().unwrap();
+        match Parser::new(tokens).parse().unwrap_err().remove(0) {
+            LoxError::ParseError(error) => error.error_type,
+            error => panic!("expected a parse error, found {:?}", error),
+        }
+    };
+    assert_eq!(
+        parse_error("1 + 2 = 3;"),
+        ParseErrorType::InvalidAssignmentTarget
+    );
+    assert_eq!(
+        parse_error("[1, 2] = 3;"),
+        ParseErrorType::InvalidAssignmentTarget
+    );
+    assert_eq!(
+        parse_error("[1 2];"),
+        ParseErrorType::UnexpectedToken {
+            expected: TokenType::RightBracket,
+            found: TokenType::Number(2.0)
+        }
+    );
+    assert_eq!(
+        parse_error("var x = ;"),
+        ParseErrorType::ExpectedExpression {
+            found: TokenType::Semicolon
+        }
+    );
+    assert_eq!(
+        parse_error("while (a) break outer;"),
+        ParseErrorType::UndefinedLabel {
+            name: "outer".into()
+        }
+    );
+    assert_eq!(
+        parse_error("outer: while (a) { fun f() { while (b) continue outer; } }"),
+        ParseErrorType::UndefinedLabel {
+            name: "outer".into()
+        }
+    );
+    assert_eq!(
+        parse_error("outer: while (a) { outer: for (;;) break; }"),
+        ParseErrorType::ShadowedLabel {
+            name: "outer".into()
+        }
+    );
+    assert_eq!(
+        parse_error(r#"print "${x y}";"#),
+        ParseErrorType::UnexpectedToken {
+            expected: TokenType::RightBrace,
+            found: TokenType::Identifier("y".into())
+        }
+    );
+    assert_eq!(
+        parse_error("outer: print 1;"),
+        ParseErrorType::UnexpectedToken {
+            expected: TokenType::While,
+            found: TokenType::Print
+        }
+    );
+    assert_eq!(
+        parse_error("if x > 1 print x;"),
+        ParseErrorType::UnexpectedToken {
+            expected: TokenType::LeftParen,
+            found: TokenType::Identifier("x".into())
+        }
+    );
+}
@@ -0,0 +1,180 @@
+use std::{
+    borrow::Cow,
+    env, fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use rustyline::{error::ReadlineError, history::DefaultHistory, Editor};
+
+use rlox::{ast_print::AstPrinter, statement::Stmt, LoxError, Parser, Scanner};
+
+use crate::{report, run, Backend};
+
+use self::helper::ReplHelper;
+
+mod helper;
+
+const PROMPT: &str = "rlox > ";
+const CONTINUATION_PROMPT: &str = "...  > ";
+
+const COMMANDS: [&str; 6] = ["help", "env", "reset", "load", "ast", "tokens"];
+
+const HELP: &str = "\
+:help           Show this message
+:env            List the global variables
+:reset          Forget everything defined so far
+:load <file>    Run a script in this session
+:ast <code>     Print the syntax tree of <code>
+:tokens <code>  Print the tokens of <code>";
+
+type LineEditor = Editor<ReplHelper, DefaultHistory>;
+
+pub fn repl(backend: &mut Backend, dump_bytecode: bool) -> rustyline::Result<()> {
+    // clear screen
+    print!("\x1B[2J\x1B[1;1H");
+    io::stdout().flush()?;
+    let mut editor = LineEditor::new()?;
+    editor.set_helper(Some(ReplHelper::default()));
+    let history = history_path();
+    if let Some(path) = &history {
+        // There is no history file until the first session ends.
+        let _ = editor.load_history(path);
+    }
+
+    loop {
+        if let Some(helper) = editor.helper_mut() {
+            helper.globals = backend
+                .globals()
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect();
+        }
+        let source = match read_entry(&mut editor) {
+            Ok(source) => source,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e),
+        };
+        if source.trim().is_empty() {
+            continue;
+        }
+        editor.add_history_entry(source.trim_end())?;
+        if let Some(command) = source.trim().strip_prefix(':') {
+            meta_command(backend, command, dump_bytecode);
+            continue;
+        }
+        let (source, stmts) = parse_entry(&source);
+        let echo = matches!(stmts.as_deref(), Ok([.., Stmt::ExprStmt(_)]));
+        // Errors have already been reported; the session carries on.
+        let _ = run(backend, &source, dump_bytecode, echo);
+    }
+
+    match &history {
+        Some(path) => editor.save_history(path),
+        None => Ok(()),
+    }
+}
+
+/// Reads lines until they make up a complete program. A blank continuation
+/// line submits the entry as it is.
+fn read_entry(editor: &mut LineEditor) -> rustyline::Result<String> {
+    let mut source = editor.readline(PROMPT)?;
+    if source.trim_start().starts_with(':') {
+        return Ok(source);
+    }
+    while is_incomplete(&source) {
+        let line = editor.readline(CONTINUATION_PROMPT)?;
+        if line.trim().is_empty() {
+            break;
+        }
+        source.push('\n');
+        source.push_str(&line);
+    }
+    Ok(source)
+}
+
+/// Whether every error in `source` comes from input ending too early, so
+/// that more lines could still fix it.
+fn is_incomplete(source: &str) -> bool {
+    match parse_entry(source).1 {
+        Ok(_) => false,
+        Err(errors) => errors.iter().all(LoxError::is_incomplete),
+    }
+}
+
+/// Parses an entry, adding the semicolon that a bare expression like `1 + 2`
+/// leaves off. Returns the source that was parsed along with the result.
+fn parse_entry(source: &str) -> (Cow<'_, str>, Result<Vec<Stmt>, Vec<LoxError>>) {
+    let result = parse(source);
+    if result.is_err() {
+        let terminated = format!("{};", source.trim_end());
+        if let Ok(stmts) = parse(&terminated) {
+            return (terminated.into(), Ok(stmts));
+        }
+    }
+    (source.into(), result)
+}
+
+fn parse(source: &str) -> Result<Vec<Stmt>, Vec<LoxError>> {
+    let tokens = Scanner::new(source.into()).scan_source()?;
+    Parser::new(tokens).parse()
+}
+
+/// Runs a `:command`, given without its colon.
+fn meta_command(backend: &mut Backend, line: &str, dump_bytecode: bool) {
+    let (command, argument) = match line.split_once(char::is_whitespace) {
+        Some((command, argument)) => (command, argument.trim()),
+        None => (line, ""),
+    };
+    match command {
+        "help" => println!("{}", HELP),
+        "env" => {
+            for (name, value) in backend.globals() {
+                println!("{} = {}", name, value);
+            }
+        }
+        "reset" => backend.reset(),
+        "load" => match fs::read_to_string(argument) {
+            Ok(source) => {
+                let _ = run(backend, &source, dump_bytecode, false);
+            }
+            Err(e) => eprintln!("Could not read `{}`: {}", argument, e),
+        },
+        "ast" => match parse_entry(argument) {
+            (_, Ok(stmts)) => {
+                for stmt in &stmts {
+                    println!("{}", AstPrinter.print_stmt(stmt));
+                }
+            }
+            (source, Err(errors)) => {
+                for e in &errors {
+                    report(e, &source);
+                }
+            }
+        },
+        "tokens" => match Scanner::new(argument.into()).scan_source() {
+            Ok(tokens) => {
+                for token in tokens {
+                    println!("{:<8}{:?}", token.span.to_string(), token.token_type);
+                }
+            }
+            Err(errors) => {
+                for e in &errors {
+                    report(e, argument);
+                }
+            }
+        },
+        _ => eprintln!("Unknown command `:{}`, see `:help`.", command),
+    }
+}
+
+/// The history file, `.rlox_history` in the user's home directory.
+fn history_path() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".rlox_history"))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,132 @@
+use std::borrow::Cow;
+
+use colored::{Color, Colorize};
+use rustyline::{
+    completion::Completer,
+    highlight::{CmdKind, Highlighter},
+    hint::Hinter,
+    validate::Validator,
+    Context, Helper,
+};
+
+use rlox::{
+    error::SyntaxErrorType,
+    token::{TokenType, KEYWORDS},
+    Scanner,
+};
+
+use super::COMMANDS;
+
+/// Highlights and completes REPL input.
+#[derive(Default)]
+pub struct ReplHelper {
+    /// Names to complete besides keywords, refreshed before each prompt.
+    pub globals: Vec<String>,
+}
+
+impl ReplHelper {
+    /// Keywords and global names starting with `prefix`.
+    fn names(&self, prefix: &str) -> Vec<String> {
+        let names = KEYWORDS
+            .keys()
+            .map(|keyword| keyword.to_string())
+            .chain(self.globals.iter().cloned());
+        matching(names, prefix)
+    }
+}
+
+fn matching(candidates: impl Iterator<Item = String>, prefix: &str) -> Vec<String> {
+    let mut candidates = candidates
+        .filter(|candidate| candidate.starts_with(prefix))
+        .collect::<Vec<_>>();
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        if line.trim_start().starts_with(':') {
+            return Cow::Borrowed(line);
+        }
+        let (tokens, errors) = Scanner::new(line.into()).scan_tokens();
+        let mut spans = tokens
+            .iter()
+            .filter_map(|token| {
+                let color = match &token.token_type {
+                    TokenType::String(_) | TokenType::Interpolation(_) => Color::Green,
+                    TokenType::Number(_) => Color::Yellow,
+                    TokenType::Identifier(_) => Color::Cyan,
+                    token_type if token_type.is_keyword() => Color::Magenta,
+                    _ => return None,
+                };
+                Some((token.span, color))
+            })
+            .chain(errors.iter().map(|error| match error.error_type {
+                // Most likely still being typed.
+                SyntaxErrorType::UnterminatedString => (error.span, Color::Green),
+                _ => (error.span, Color::Red),
+            }))
+            .collect::<Vec<_>>();
+        spans.sort_by_key(|(span, _)| span.start);
+
+        let mut highlighted = String::new();
+        let mut end = 0;
+        for (span, color) in spans {
+            highlighted.push_str(&line[end..span.start]);
+            highlighted.push_str(&line[span.start..span.end].color(color).to_string());
+            end = span.end;
+        }
+        highlighted.push_str(&line[end..]);
+        Cow::Owned(highlighted)
+    }
+
+    fn highlight_prompt<'b, 's: 'b, 'p: 'b>(
+        &'s self,
+        prompt: &'p str,
+        _default: bool,
+    ) -> Cow<'b, str> {
+        let (name, arrow) = prompt.split_at(prompt.len() - " > ".len());
+        Cow::Owned(format!("{}{}", name.bold().green(), arrow.purple()))
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, kind: CmdKind) -> bool {
+        kind != CmdKind::MoveCursor
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let before = &line[..pos];
+        if before.starts_with(':') && !before.contains(char::is_whitespace) {
+            let commands = COMMANDS.iter().map(|command| format!(":{}", command));
+            return Ok((0, matching(commands, before)));
+        }
+        let word_len = before
+            .chars()
+            .rev()
+            .take_while(|&c| c.is_ascii_alphanumeric() || c == '_')
+            .map(char::len_utf8)
+            .sum::<usize>();
+        let start = pos - word_len;
+        Ok((start, self.names(&before[start..])))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+#[cfg(test)]
+mod tests;
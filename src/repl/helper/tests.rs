@@ -0,0 +1,67 @@
+use rustyline::{history::DefaultHistory, Context};
+
+use super::*;
+
+fn complete(helper: &ReplHelper, line: &str) -> (usize, Vec<String>) {
+    let history = DefaultHistory::new();
+    helper
+        .complete(line, line.len(), &Context::new(&history))
+        .unwrap()
+}
+
+#[test]
+fn test_complete_keywords_and_globals() {
+    let helper = ReplHelper {
+        globals: vec!["printer".into(), "clock".into(), "classes".into()],
+    };
+    assert_eq!(
+        complete(&helper, "pri"),
+        (0, vec!["print".into(), "printer".into()])
+    );
+    assert_eq!(
+        complete(&helper, "var x = cl"),
+        (8, vec!["class".into(), "classes".into(), "clock".into()])
+    );
+    assert_eq!(
+        complete(&helper, "x + "),
+        (4, {
+            let mut all = KEYWORDS.keys().map(|k| k.to_string()).collect::<Vec<_>>();
+            all.extend(helper.globals.iter().cloned());
+            all.sort();
+            all
+        })
+    );
+}
+
+#[test]
+fn test_complete_commands() {
+    let helper = ReplHelper::default();
+    assert_eq!(complete(&helper, ":re"), (0, vec![":reset".into()]));
+    assert_eq!(complete(&helper, ":ast pr"), (5, vec!["print".into()]));
+}
+
+/// Removes ANSI color codes.
+fn strip_colors(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+#[test]
+fn test_highlight_keeps_text() {
+    colored::control::set_override(true);
+    let helper = ReplHelper::default();
+    let line = "var s = \"hi\" @ 1; // done";
+    let highlighted = helper.highlight(line, 0);
+    assert!(highlighted.contains(&"var".magenta().to_string()));
+    assert!(highlighted.contains(&"\"hi\"".green().to_string()));
+    assert!(highlighted.contains(&"@".red().to_string()));
+    assert_eq!(strip_colors(&highlighted), line);
+}
@@ -0,0 +1,35 @@
+use super::{is_incomplete, parse_entry};
+
+#[test]
+fn test_incomplete_input() {
+    assert!(is_incomplete("{ print 1;"));
+    assert!(is_incomplete("if (x"));
+    assert!(is_incomplete("print 1 +"));
+    assert!(is_incomplete("fun f() {\n  print \"hi\";"));
+    assert!(is_incomplete("print \"unterminated"));
+}
+
+#[test]
+fn test_complete_input() {
+    assert!(!is_incomplete("print 1;"));
+    assert!(!is_incomplete("{ print 1; }"));
+    // Errors that more input cannot fix are reported straight away.
+    assert!(!is_incomplete("print ;"));
+    assert!(!is_incomplete("print ; {"));
+}
+
+#[test]
+fn test_bare_expression() {
+    assert!(!is_incomplete("1 + 2"));
+    let (source, stmts) = parse_entry("1 + 2  ");
+    assert_eq!(source, "1 + 2;");
+    assert!(stmts.is_ok());
+
+    let (source, stmts) = parse_entry("var x = 1;");
+    assert_eq!(source, "var x = 1;");
+    assert!(stmts.is_ok());
+
+    let (source, stmts) = parse_entry("print ;");
+    assert_eq!(source, "print ;");
+    assert!(stmts.is_err());
+}
@@ -0,0 +1,361 @@
+use std::{cell::Cell, rc::Rc};
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    error::{LoxError, ResolveError, ResolveErrorType},
+    expression::{
+        Assign, Binary, Call, Expression, ExpressionVisitor, Get, Grouping, Index, Interpolation,
+        List, Literal, Map, Set, Super, This, Unary, Var,
+    },
+    span::Span,
+    statement::{
+        BlockStmt, BreakStmt, ClassDecl, ContinueStmt, ExprStmt, FunDecl, IfStmt, PrintStmt,
+        ReturnStmt, Stmt, StmtVisitor, VarDecl, WhileStmt,
+    },
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionType {
+    None,
+    Function,
+    Method,
+    Initializer,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
+/// A name declared in a local scope.
+struct Local {
+    /// Whether the initializer has finished.
+    defined: bool,
+    span: Span,
+}
+
+/// Binds every local variable use to the scope that declares it before the
+/// program runs, and reports errors that can be caught statically.
+pub struct Resolver {
+    /// Names declared in each enclosing local scope. Globals are not tracked.
+    scopes: Vec<FxHashMap<String, Local>>,
+    function: FunctionType,
+    class: ClassType,
+    /// Whether `break` and `continue` are allowed, i.e. the closest enclosing
+    /// loop is inside the current function.
+    in_loop: bool,
+    errors: Vec<LoxError>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self {
+            scopes: Vec::new(),
+            function: FunctionType::None,
+            class: ClassType::None,
+            in_loop: false,
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl Resolver {
+    pub fn resolve(mut self, stmts: &[Stmt]) -> Result<(), Vec<LoxError>> {
+        self.resolve_stmts(stmts);
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    fn resolve_stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            stmt.accept(&mut *self);
+        }
+    }
+
+    fn resolve_expr(&mut self, expr: &Expression) {
+        expr.accept(self)
+    }
+
+    fn resolve_function(&mut self, function: &FunDecl, function_type: FunctionType) {
+        let enclosing = std::mem::replace(&mut self.function, function_type);
+        let in_loop = std::mem::replace(&mut self.in_loop, false);
+
+        self.begin_scope();
+        for param in &function.params {
+            self.declare(param, function.span);
+            self.define(param, function.span);
+        }
+        self.resolve_stmts(&function.body);
+        self.end_scope();
+
+        self.function = enclosing;
+        self.in_loop = in_loop;
+    }
+
+    /// Records how many scopes up `name` was declared. Names not found in any
+    /// local scope are left unresolved and treated as globals.
+    fn resolve_local(&mut self, depth: &Cell<Option<usize>>, name: &str) {
+        let found = self
+            .scopes
+            .iter()
+            .rev()
+            .position(|scope| scope.contains_key(name));
+        depth.set(found);
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(FxHashMap::default());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &str, span: Span) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+        let local = Local {
+            defined: false,
+            span,
+        };
+        if let Some(previous) = scope.insert(name.into(), local) {
+            self.error(
+                ResolveErrorType::AlreadyDeclared {
+                    name: name.into(),
+                    previous: previous.span,
+                },
+                span,
+            );
+        }
+    }
+
+    fn define(&mut self, name: &str, span: Span) {
+        if let Some(scope) = self.scopes.last_mut() {
+            let local = Local {
+                defined: true,
+                span,
+            };
+            scope.insert(name.into(), local);
+        }
+    }
+
+    fn error(&mut self, error_type: ResolveErrorType, span: Span) {
+        self.errors.push(ResolveError { error_type, span }.into());
+    }
+}
+
+impl ExpressionVisitor for Resolver {
+    type Return = ();
+    fn visit_literal(&mut self, _inner: &Literal) -> Self::Return {}
+
+    fn visit_unary(&mut self, inner: &Unary) -> Self::Return {
+        self.resolve_expr(&inner.expr);
+    }
+
+    fn visit_binary(&mut self, inner: &Binary) -> Self::Return {
+        self.resolve_expr(&inner.left);
+        self.resolve_expr(&inner.right);
+    }
+
+    fn visit_grouping(&mut self, inner: &Grouping) -> Self::Return {
+        self.resolve_expr(&inner.expr);
+    }
+
+    fn visit_var(&mut self, inner: &Var) -> Self::Return {
+        let in_initializer = self
+            .scopes
+            .last()
+            .and_then(|scope| scope.get(&inner.name))
+            .is_some_and(|local| !local.defined);
+        if in_initializer {
+            self.error(
+                ResolveErrorType::ReadInOwnInitializer {
+                    name: inner.name.clone(),
+                },
+                inner.span,
+            );
+        }
+        self.resolve_local(&inner.depth, &inner.name);
+    }
+
+    fn visit_assign(&mut self, inner: &Assign) -> Self::Return {
+        self.resolve_expr(&inner.value);
+        match inner.target.as_ref() {
+            Expression::Var(var) => self.resolve_local(&var.depth, &var.name),
+            target => self.resolve_expr(target),
+        }
+    }
+
+    fn visit_list(&mut self, inner: &List) -> Self::Return {
+        for element in &inner.elements {
+            self.resolve_expr(element);
+        }
+    }
+
+    fn visit_map(&mut self, inner: &Map) -> Self::Return {
+        for (key, value) in &inner.entries {
+            self.resolve_expr(key);
+            self.resolve_expr(value);
+        }
+    }
+
+    fn visit_interpolation(&mut self, inner: &Interpolation) -> Self::Return {
+        for part in &inner.parts {
+            self.resolve_expr(part);
+        }
+    }
+
+    fn visit_index(&mut self, inner: &Index) -> Self::Return {
+        self.resolve_expr(&inner.object);
+        self.resolve_expr(&inner.index);
+    }
+
+    fn visit_call(&mut self, inner: &Call) -> Self::Return {
+        self.resolve_expr(&inner.callee);
+        for arg in &inner.arguments {
+            self.resolve_expr(arg);
+        }
+    }
+
+    fn visit_get(&mut self, inner: &Get) -> Self::Return {
+        self.resolve_expr(&inner.object);
+    }
+
+    fn visit_set(&mut self, inner: &Set) -> Self::Return {
+        self.resolve_expr(&inner.value);
+        self.resolve_expr(&inner.object);
+    }
+
+    fn visit_this(&mut self, inner: &This) -> Self::Return {
+        if self.class == ClassType::None {
+            self.error(ResolveErrorType::ThisOutsideClass, inner.span);
+            return;
+        }
+        self.resolve_local(&inner.depth, "this");
+    }
+
+    fn visit_super(&mut self, inner: &Super) -> Self::Return {
+        match self.class {
+            ClassType::None => self.error(ResolveErrorType::SuperOutsideClass, inner.span),
+            ClassType::Class => self.error(ResolveErrorType::SuperWithoutSuperclass, inner.span),
+            ClassType::Subclass => self.resolve_local(&inner.depth, "super"),
+        }
+    }
+}
+
+impl StmtVisitor for Resolver {
+    type Return = ();
+    fn visit_expr_stmt(&mut self, inner: &ExprStmt) -> Self::Return {
+        self.resolve_expr(&inner.expr);
+    }
+
+    fn visit_print_stmt(&mut self, inner: &PrintStmt) -> Self::Return {
+        self.resolve_expr(&inner.expr);
+    }
+
+    fn visit_var_decl(&mut self, inner: &VarDecl) -> Self::Return {
+        self.declare(&inner.name, inner.span);
+        if let Some(initializer) = &inner.initializer {
+            self.resolve_expr(initializer);
+        }
+        self.define(&inner.name, inner.span);
+    }
+
+    fn visit_fun_decl(&mut self, inner: &Rc<FunDecl>) -> Self::Return {
+        // Defined eagerly so the function can refer to itself recursively.
+        self.declare(&inner.name, inner.span);
+        self.define(&inner.name, inner.span);
+        self.resolve_function(inner, FunctionType::Function);
+    }
+
+    fn visit_class_decl(&mut self, inner: &ClassDecl) -> Self::Return {
+        let enclosing = std::mem::replace(&mut self.class, ClassType::Class);
+        self.declare(&inner.name, inner.span);
+        self.define(&inner.name, inner.span);
+
+        if let Some(superclass) = &inner.superclass {
+            if superclass.name == inner.name {
+                let name = inner.name.clone();
+                self.error(ResolveErrorType::InheritFromSelf { name }, superclass.span);
+            }
+            self.class = ClassType::Subclass;
+            self.visit_var(superclass);
+            self.begin_scope();
+            self.define("super", superclass.span);
+        }
+
+        self.begin_scope();
+        self.define("this", inner.span);
+        for method in &inner.methods {
+            let function_type = match method.name.as_str() {
+                "init" => FunctionType::Initializer,
+                _ => FunctionType::Method,
+            };
+            self.resolve_function(method, function_type);
+        }
+        self.end_scope();
+
+        if inner.superclass.is_some() {
+            self.end_scope();
+        }
+        self.class = enclosing;
+    }
+
+    fn visit_block_stmt(&mut self, inner: &BlockStmt) -> Self::Return {
+        self.begin_scope();
+        self.resolve_stmts(&inner.stmts);
+        self.end_scope();
+    }
+
+    fn visit_if_stmt(&mut self, inner: &IfStmt) -> Self::Return {
+        self.resolve_expr(&inner.condition);
+        inner.then_branch.accept(&mut *self);
+        if let Some(else_branch) = &inner.else_branch {
+            else_branch.accept(&mut *self);
+        }
+    }
+
+    fn visit_while_stmt(&mut self, inner: &WhileStmt) -> Self::Return {
+        self.resolve_expr(&inner.condition);
+        let in_loop = std::mem::replace(&mut self.in_loop, true);
+        inner.body.accept(&mut *self);
+        self.in_loop = in_loop;
+        if let Some(increment) = &inner.increment {
+            self.resolve_expr(increment);
+        }
+    }
+
+    fn visit_return(&mut self, inner: &ReturnStmt) -> Self::Return {
+        if self.function == FunctionType::None {
+            self.error(ResolveErrorType::TopLevelReturn, inner.span);
+        }
+        if let Some(value) = &inner.value {
+            if self.function == FunctionType::Initializer {
+                self.error(ResolveErrorType::ReturnFromInitializer, inner.span);
+            }
+            self.resolve_expr(value);
+        }
+    }
+
+    fn visit_break(&mut self, inner: &BreakStmt) -> Self::Return {
+        if !self.in_loop {
+            self.error(ResolveErrorType::BreakOutsideLoop, inner.span);
+        }
+    }
+
+    fn visit_continue(&mut self, inner: &ContinueStmt) -> Self::Return {
+        if !self.in_loop {
+            self.error(ResolveErrorType::ContinueOutsideLoop, inner.span);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
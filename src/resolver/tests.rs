@@ -0,0 +1,93 @@
+use super::*;
+use crate::{parser::Parser, scanner::Scanner};
+
+fn resolve(source: &'static str) -> Result<Vec<Stmt>, Vec<String>> {
+    let tokens = Scanner::new(source.to_string()).scan_source().unwrap();
+    let stmts = Parser::new(tokens).parse().unwrap();
+    match Resolver::default().resolve(&stmts) {
+        Ok(()) => Ok(stmts),
+        Err(errs) => Err(errs.iter().map(|e| e.to_string()).collect()),
+    }
+}
+
+#[test]
+fn test_resolve_depth() {
+    let stmts = resolve(
+        r#"
+        var global = 1;
+        {
+            var outer = 2;
+            {
+                print outer + global;
+            }
+        }
+    "#,
+    )
+    .unwrap();
+
+    let Stmt::BlockStmt(outer) = &stmts[1] else {
+        panic!()
+    };
+    let Stmt::BlockStmt(inner) = &outer.stmts[1] else {
+        panic!()
+    };
+    let Stmt::PrintStmt(PrintStmt {
+        expr: Expression::Binary(binary),
+        ..
+    }) = &inner.stmts[0]
+    else {
+        panic!()
+    };
+    let (Expression::Var(outer_var), Expression::Var(global_var)) =
+        (binary.left.as_ref(), binary.right.as_ref())
+    else {
+        panic!()
+    };
+    assert_eq!(outer_var.depth.get(), Some(1));
+    assert_eq!(global_var.depth.get(), None);
+}
+
+#[test]
+fn test_resolve_errors() {
+    assert_eq!(
+        resolve("{\n var a = 1;\n { var a = a; }\n}").unwrap_err(),
+        vec!["line 3:12: Cannot read local variable `a` in its own initializer"]
+    );
+    assert_eq!(
+        resolve("{\n var a = 1;\n var a = 2;\n}").unwrap_err(),
+        vec!["line 3:2: `a` is already declared in this scope"]
+    );
+    assert_eq!(
+        resolve("return 1;").unwrap_err(),
+        vec!["line 1:1: Cannot return from top-level code"]
+    );
+    assert_eq!(
+        resolve("class A { init() { return 1; } }").unwrap_err(),
+        vec!["line 1:20: Cannot return a value from an initializer"]
+    );
+    assert_eq!(
+        resolve("print this;").unwrap_err(),
+        vec!["line 1:7: Cannot use `this` outside of a class"]
+    );
+    assert_eq!(
+        resolve("class A { f() { super.f(); } }").unwrap_err(),
+        vec!["line 1:17: Cannot use `super` in a class with no superclass"]
+    );
+    assert_eq!(
+        resolve("class A {}\n{ class A < A {} }").unwrap_err(),
+        vec!["line 2:13: Class `A` cannot inherit from itself"]
+    );
+    assert_eq!(
+        resolve("if (true) break;").unwrap_err(),
+        vec!["line 1:11: Cannot use `break` outside of a loop"]
+    );
+    assert_eq!(
+        resolve("while (true) { fun f() { continue; } }").unwrap_err(),
+        vec!["line 1:26: Cannot use `continue` outside of a loop"]
+    );
+}
+
+#[test]
+fn test_resolve_global_redeclaration() {
+    assert!(resolve("var a = 1; var a = 2;").is_ok());
+}
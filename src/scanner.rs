@@ -1,13 +1,20 @@
-use std::{iter::Peekable, str::Chars};
+use std::{iter::Peekable, str::CharIndices};
 
 use crate::{
-    error::SyntaxError,
+    error::{LoxError, SyntaxError, SyntaxErrorType},
+    span::Span,
     token::{Token, TokenType, KEYWORDS},
 };
 
 pub struct Scanner {
     source: String,
     line_num: u32,
+    /// The byte offset and column of the last token start on the current
+    /// line, so columns are counted from there rather than the line start.
+    column_mark: (usize, u32),
+    /// How many braces are open inside each `${` being scanned, innermost
+    /// last. The `}` closing an interpolation resumes its string.
+    interpolations: Vec<u32>,
 }
 
 impl Scanner {
@@ -15,180 +22,245 @@ impl Scanner {
         Scanner {
             source,
             line_num: 1,
+            column_mark: (0, 1),
+            interpolations: Vec::new(),
         }
     }
 
-    pub fn scan_source(mut self) -> Result<Vec<Token>, Vec<SyntaxError>> {
+    pub fn scan_source(self) -> Result<Vec<Token>, Vec<LoxError>> {
+        let (tokens, errors) = self.scan_tokens();
+        if errors.is_empty() {
+            Ok(tokens)
+        } else {
+            Err(errors.into_iter().map(LoxError::from).collect())
+        }
+    }
+
+    /// Scans the whole source, keeping the tokens found around any errors,
+    /// e.g. to highlight input that is still being typed.
+    pub fn scan_tokens(mut self) -> (Vec<Token>, Vec<SyntaxError>) {
         let mut tokens = Vec::new();
         let mut errors = Vec::new();
         let source = std::mem::take(&mut self.source);
+        let mut chars = source.char_indices().peekable();
 
-        'lines: for line_text in source.lines() {
-            let mut line = line_text.chars().peekable();
-            loop {
-                let Some(c) = line.next() else { break };
-                match c {
-                    // Single-character tokens.
-                    ' ' | '\t' | '\r' => continue,
-                    '(' => tokens.push(Token::new(TokenType::LeftParen, self.line_num)),
-                    ')' => tokens.push(Token::new(TokenType::RightParen, self.line_num)),
-                    '{' => tokens.push(Token::new(TokenType::LeftBrace, self.line_num)),
-                    '}' => tokens.push(Token::new(TokenType::RightBrace, self.line_num)),
-                    ',' => tokens.push(Token::new(TokenType::Comma, self.line_num)),
-                    '.' => tokens.push(Token::new(TokenType::Dot, self.line_num)),
-                    '-' => tokens.push(Token::new(TokenType::Minus, self.line_num)),
-                    '+' => tokens.push(Token::new(TokenType::Plus, self.line_num)),
-                    ';' => tokens.push(Token::new(TokenType::Semicolon, self.line_num)),
-                    '*' => tokens.push(Token::new(TokenType::Star, self.line_num)),
-                    // One or two character tokens.
-                    '!' => match line.peek() {
-                        Some('=') => {
-                            line.next();
-                            tokens.push(Token::new(TokenType::BangEqual, self.line_num));
-                        }
-                        Some(_) => {
-                            tokens.push(Token::new(TokenType::Bang, self.line_num));
-                        }
-                        None => errors.push(SyntaxError {
-                            line_num: self.line_num,
-                        }),
-                    },
-                    '=' => match line.peek() {
-                        Some('=') => {
-                            line.next();
-                            tokens.push(Token::new(TokenType::EqualEqual, self.line_num));
-                        }
-                        Some(_) => {
-                            tokens.push(Token::new(TokenType::Equal, self.line_num));
-                        }
-                        None => errors.push(SyntaxError {
-                            line_num: self.line_num,
-                        }),
-                    },
-                    '>' => match line.peek() {
-                        Some('=') => {
-                            line.next();
-                            tokens.push(Token::new(TokenType::GreaterEqual, self.line_num));
-                        }
-                        Some(_) => {
-                            tokens.push(Token::new(TokenType::Greater, self.line_num));
-                        }
-                        None => errors.push(SyntaxError {
-                            line_num: self.line_num,
-                        }),
-                    },
-                    '<' => match line.peek() {
-                        Some('=') => {
-                            line.next();
-                            tokens.push(Token::new(TokenType::LessEqual, self.line_num));
-                        }
-                        Some(_) => {
-                            tokens.push(Token::new(TokenType::Less, self.line_num));
-                        }
-                        None => errors.push(SyntaxError {
-                            line_num: self.line_num,
-                        }),
-                    },
-                    '/' => match line.peek() {
-                        Some('/') => {
-                            self.line_num += 1;
-                            continue 'lines;
-                        }
-                        Some(_) => {
-                            tokens.push(Token::new(TokenType::Slash, self.line_num));
-                        }
-                        None => errors.push(SyntaxError {
-                            line_num: self.line_num,
-                        }),
-                    },
-                    // Literals.
-                    '"' | '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' => {
-                        match self.scan_literal(&mut line, c) {
-                            Ok(t) => tokens.push(t),
-                            Err(e) => errors.push(e),
-                        }
+        while let Some((mut start, c)) = chars.next() {
+            // Tokens spanning several lines are reported where they begin.
+            let line = self.line_num;
+            let mut column = self.column(&source, start);
+            let token_type = match c {
+                // Single-character tokens.
+                ' ' | '\t' | '\r' => continue,
+                '\n' => {
+                    self.new_line(start);
+                    continue;
+                }
+                '(' => Ok(TokenType::LeftParen),
+                ')' => Ok(TokenType::RightParen),
+                '{' => {
+                    if let Some(open) = self.interpolations.last_mut() {
+                        *open += 1;
+                    }
+                    Ok(TokenType::LeftBrace)
+                }
+                '}' => match self.interpolations.last_mut() {
+                    // The `}` gets a token of its own, so that `${}` is told
+                    // apart from a string literal right after the `${`.
+                    Some(0) => {
+                        self.interpolations.pop();
+                        let span = Span::new(start, start + 1, line, column);
+                        tokens.push(Token::new(TokenType::RightBrace, span));
+                        (start, column) = (start + 1, column + 1);
+                        self.scan_string(&mut chars, false)
                     }
-                    c if c.is_ascii_alphanumeric() => match self.scan_identifier(&mut line, c) {
-                        Ok(t) => tokens.push(t),
-                        Err(e) => errors.push(e),
-                    },
-                    _ => errors.push(SyntaxError {
-                        line_num: self.line_num,
-                    }),
-                };
+                    Some(open) => {
+                        *open -= 1;
+                        Ok(TokenType::RightBrace)
+                    }
+                    None => Ok(TokenType::RightBrace),
+                },
+                '[' => Ok(TokenType::LeftBracket),
+                ']' => Ok(TokenType::RightBracket),
+                ':' => Ok(TokenType::Colon),
+                ',' => Ok(TokenType::Comma),
+                '.' => Ok(TokenType::Dot),
+                '-' => Ok(TokenType::Minus),
+                '+' => Ok(TokenType::Plus),
+                ';' => Ok(TokenType::Semicolon),
+                '*' => Ok(TokenType::Star),
+                // One or two character tokens.
+                '!' => match chars.next_if(|&(_, c)| c == '=') {
+                    Some(_) => Ok(TokenType::BangEqual),
+                    None => Ok(TokenType::Bang),
+                },
+                '=' => match chars.next_if(|&(_, c)| c == '=') {
+                    Some(_) => Ok(TokenType::EqualEqual),
+                    None => Ok(TokenType::Equal),
+                },
+                '>' => match chars.next_if(|&(_, c)| c == '=') {
+                    Some(_) => Ok(TokenType::GreaterEqual),
+                    None => Ok(TokenType::Greater),
+                },
+                '<' => match chars.next_if(|&(_, c)| c == '=') {
+                    Some(_) => Ok(TokenType::LessEqual),
+                    None => Ok(TokenType::Less),
+                },
+                '/' => match chars.next_if(|&(_, c)| c == '/') {
+                    Some(_) => {
+                        while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+                        continue;
+                    }
+                    None => Ok(TokenType::Slash),
+                },
+                // Literals.
+                'r' if chars.next_if(|&(_, c)| c == '"').is_some() => {
+                    self.scan_string(&mut chars, true)
+                }
+                '"' | '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' => {
+                    self.scan_literal(&mut chars, c)
+                }
+                c if c.is_ascii_alphanumeric() => Ok(self.scan_identifier(&mut chars, c)),
+                found => Err(SyntaxErrorType::UnexpectedCharacter { found }),
+            };
+
+            let end = chars.peek().map_or(source.len(), |&(i, _)| i);
+            let span = Span::new(start, end, line, column);
+            match token_type {
+                Ok(token_type) => tokens.push(Token::new(token_type, span)),
+                Err(error_type) => errors.push(SyntaxError { error_type, span }),
             }
-            self.line_num += 1;
         }
 
-        if errors.is_empty() {
-            tokens.push(Token::new(TokenType::EOF, self.line_num - 1));
-            Ok(tokens)
-        } else {
-            Err(errors)
-        }
+        // `EOF` goes at the end of the last line, even after a final newline.
+        let end = source.len();
+        let line = source.split_inclusive('\n').count().max(1) as u32;
+        let column = source.lines().last().map_or(0, |l| l.chars().count()) as u32 + 1;
+        let span = Span::new(end, end, line, column);
+        tokens.push(Token::new(TokenType::EOF, span));
+        (tokens, errors)
+    }
+
+    /// Records the line break at byte offset `newline`.
+    fn new_line(&mut self, newline: usize) {
+        self.line_num += 1;
+        self.column_mark = (newline + 1, 1);
+    }
+
+    /// The column of byte offset `offset` on the current line, which must not
+    /// come before the last offset asked about.
+    fn column(&mut self, source: &str, offset: usize) -> u32 {
+        let (mark, column) = self.column_mark;
+        let column = column + source[mark..offset].chars().count() as u32;
+        self.column_mark = (offset, column);
+        column
     }
 
     fn scan_literal(
         &mut self,
-        iter: &mut Peekable<Chars>,
+        iter: &mut Peekable<CharIndices>,
         current: char,
-    ) -> Result<Token, SyntaxError> {
+    ) -> Result<TokenType, SyntaxErrorType> {
         match current {
-            '"' => {
-                if iter.peek().is_none() {
-                    return Err(SyntaxError {
-                        line_num: self.line_num,
-                    });
-                }
-                let mut s = String::new();
-                for c in iter {
-                    if c == '"' {
-                        break;
-                    }
-                    s.push(c);
-                    if c == '\n' {
-                        self.line_num += 1
-                    }
-                }
-                Ok(Token::new(TokenType::String(s), self.line_num))
-            }
+            '"' => self.scan_string(iter, false),
             digit if digit.is_ascii_digit() => {
                 let mut number = digit.to_string();
-                while let Some(d) = iter.peek() {
-                    if !d.is_ascii_digit() && *d != '.' {
+                while let Some(&(_, d)) = iter.peek() {
+                    if !d.is_ascii_digit() && d != '.' {
                         break;
                     }
-                    number.push(iter.next().unwrap());
-                }
-                match number.parse::<f64>() {
-                    Ok(n) => Ok(Token::new(TokenType::Number(n), self.line_num)),
-                    Err(_) => Err(SyntaxError {
-                        line_num: self.line_num,
-                    }),
+                    number.push(d);
+                    iter.next();
                 }
+                number
+                    .parse::<f64>()
+                    .map(TokenType::Number)
+                    .map_err(|_| SyntaxErrorType::InvalidNumber)
             }
             _ => todo!(),
         }
     }
 
-    fn scan_identifier(
+    /// Scans the rest of a string up to its closing quote, or up to the next
+    /// `${`. Raw strings have neither escapes nor interpolation.
+    fn scan_string(
         &mut self,
-        iter: &mut Peekable<Chars>,
-        current: char,
-    ) -> Result<Token, SyntaxError> {
+        iter: &mut Peekable<CharIndices>,
+        raw: bool,
+    ) -> Result<TokenType, SyntaxErrorType> {
+        let mut s = String::new();
+        // Reported once the whole string is consumed, so scanning resumes
+        // after it.
+        let mut error = None;
+        while let Some((i, c)) = iter.next() {
+            match c {
+                '"' => return error.map_or(Ok(TokenType::String(s)), Err),
+                '\n' => {
+                    self.new_line(i);
+                    s.push(c);
+                }
+                '$' if !raw && iter.next_if(|&(_, c)| c == '{').is_some() => {
+                    self.interpolations.push(0);
+                    return error.map_or(Ok(TokenType::Interpolation(s)), Err);
+                }
+                '\\' if !raw => match scan_escape(iter) {
+                    Ok(c) => s.push(c),
+                    Err(e) => {
+                        error.get_or_insert(e);
+                    }
+                },
+                c => s.push(c),
+            }
+        }
+        Err(SyntaxErrorType::UnterminatedString)
+    }
+
+    fn scan_identifier(&mut self, iter: &mut Peekable<CharIndices>, current: char) -> TokenType {
         let mut token = current.to_string();
-        while let Some(n) = iter.peek() {
-            if matches!(n, &'\n' | &'\t' | &' ' | '\r') || !(n.is_ascii_alphanumeric() || &'_' == n)
-            {
+        while let Some(&(_, n)) = iter.peek() {
+            if matches!(n, '\n' | '\t' | ' ' | '\r') || !(n.is_ascii_alphanumeric() || '_' == n) {
                 break;
             }
-            token.push(iter.next().unwrap());
+            token.push(n);
+            iter.next();
         }
-        if let Some(token_type) = KEYWORDS.get(token.as_str()) {
-            Ok(Token::new(token_type.clone(), self.line_num))
-        } else {
-            Ok(Token::new(TokenType::Identifier(token), self.line_num))
+        match KEYWORDS.get(token.as_str()) {
+            Some(token_type) => token_type.clone(),
+            None => TokenType::Identifier(token),
+        }
+    }
+}
+
+/// Scans an escape sequence after its backslash. A line break is left for
+/// the caller, which counts lines.
+fn scan_escape(iter: &mut Peekable<CharIndices>) -> Result<char, SyntaxErrorType> {
+    let invalid = |sequence: String| SyntaxErrorType::InvalidEscape { sequence };
+    match iter.next_if(|&(_, c)| c != '\n') {
+        Some((_, 'n')) => Ok('\n'),
+        Some((_, 't')) => Ok('\t'),
+        Some((_, '"')) => Ok('"'),
+        Some((_, '\\')) => Ok('\\'),
+        Some((_, '$')) => Ok('$'),
+        Some((_, 'u')) => {
+            let mut sequence = "\\u".to_string();
+            if iter.next_if(|&(_, c)| c == '{').is_none() {
+                return Err(invalid(sequence));
+            }
+            sequence.push('{');
+            while let Some((_, c)) = iter.next_if(|&(_, c)| c.is_ascii_hexdigit()) {
+                sequence.push(c);
+            }
+            if iter.next_if(|&(_, c)| c == '}').is_none() {
+                return Err(invalid(sequence));
+            }
+            sequence.push('}');
+            u32::from_str_radix(&sequence[3..sequence.len() - 1], 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| invalid(sequence))
         }
+        Some((_, c)) => Err(invalid(format!("\\{}", c))),
+        None if iter.peek().is_some() => Err(invalid("\\".into())),
+        None => Err(SyntaxErrorType::UnterminatedString),
     }
 }
 
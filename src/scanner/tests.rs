@@ -1,5 +1,13 @@
 use super::*;
 
+/// Drops columns and offsets so tests can focus on token types and lines.
+fn types_and_lines(tokens: Vec<Token>) -> Vec<(TokenType, u32)> {
+    tokens
+        .into_iter()
+        .map(|t| (t.token_type, t.span.line))
+        .collect()
+}
+
 #[test]
 fn scan_string_test() {
     let source = r#"
@@ -9,15 +17,109 @@ fn scan_string_test() {
     let scanner = Scanner::new(source.to_string());
     let tokens = scanner.scan_source().unwrap();
     assert_eq!(
-        tokens,
+        types_and_lines(tokens),
+        vec![
+            (TokenType::String("hello, world".to_string()), 2),
+            (TokenType::Semicolon, 2),
+            (TokenType::String("hello again!".to_string()), 3),
+            (TokenType::Semicolon, 3),
+            (TokenType::EOF, 4),
+        ]
+    );
+}
+
+#[test]
+fn scan_interpolation_test() {
+    let source = r#""a ${b + "${c}"} d ${{}}$e""#;
+    let tokens = Scanner::new(source.to_string()).scan_source().unwrap();
+    assert_eq!(
+        types_and_lines(tokens),
+        vec![
+            (TokenType::Interpolation("a ".to_string()), 1),
+            (TokenType::Identifier("b".to_string()), 1),
+            (TokenType::Plus, 1),
+            (TokenType::Interpolation("".to_string()), 1),
+            (TokenType::Identifier("c".to_string()), 1),
+            (TokenType::RightBrace, 1),
+            (TokenType::String("".to_string()), 1),
+            (TokenType::RightBrace, 1),
+            (TokenType::Interpolation(" d ".to_string()), 1),
+            (TokenType::LeftBrace, 1),
+            (TokenType::RightBrace, 1),
+            (TokenType::RightBrace, 1),
+            (TokenType::String("$e".to_string()), 1),
+            (TokenType::EOF, 1),
+        ]
+    );
+}
+
+#[test]
+fn scan_multiline_string_test() {
+    let source = "print \"one\ntwo\";\nx;\n\"open\nstill open";
+    let (tokens, errors) = Scanner::new(source.to_string()).scan_tokens();
+    assert_eq!(
+        types_and_lines(tokens),
         vec![
-            Token::new(TokenType::String("hello, world".to_string()), 2),
-            Token::new(TokenType::Semicolon, 2),
-            Token::new(TokenType::String("hello again!".to_string()), 3),
-            Token::new(TokenType::Semicolon, 3),
-            Token::new(TokenType::EOF, 4),
+            (TokenType::Print, 1),
+            (TokenType::String("one\ntwo".to_string()), 1),
+            (TokenType::Semicolon, 2),
+            (TokenType::Identifier("x".to_string()), 3),
+            (TokenType::Semicolon, 3),
+            (TokenType::EOF, 5),
+        ]
+    );
+    assert_eq!(
+        errors,
+        vec![SyntaxError {
+            error_type: SyntaxErrorType::UnterminatedString,
+            span: Span::new(20, 36, 4, 1)
+        }]
+    );
+}
+
+#[test]
+fn scan_escape_test() {
+    let source = r#""a\tb\n\"c\" \\ \${x} \u{48}\u{1F600}" r"C:\new\${x}" "#;
+    let tokens = Scanner::new(source.to_string()).scan_source().unwrap();
+    assert_eq!(
+        types_and_lines(tokens),
+        vec![
+            (TokenType::String("a\tb\n\"c\" \\ ${x} H😀".to_string()), 1),
+            (TokenType::String(r"C:\new\${x}".to_string()), 1),
+            (TokenType::EOF, 1),
+        ]
+    );
+
+    let error_types = |source: &str| {
+        let (tokens, errors) = Scanner::new(source.to_string()).scan_tokens();
+        // Scanning carries on after the bad string.
+        assert_eq!(tokens.len(), 2);
+        errors.into_iter().map(|e| e.error_type).collect::<Vec<_>>()
+    };
+    let invalid = |sequence: &str| SyntaxErrorType::InvalidEscape {
+        sequence: sequence.into(),
+    };
+    assert_eq!(error_types(r#""\q" x"#), [invalid(r"\q")]);
+    assert_eq!(error_types(r#""\u41" x"#), [invalid(r"\u")]);
+    assert_eq!(error_types(r#""\u{41" x"#), [invalid(r"\u{41")]);
+    assert_eq!(error_types(r#""\u{D800}\z" x"#), [invalid(r"\u{D800}")]);
+
+    // The line break after a bare backslash still counts.
+    let (tokens, errors) = Scanner::new("print \"a\\\nb\"; @".into()).scan_tokens();
+    assert_eq!(
+        errors,
+        [
+            SyntaxError {
+                error_type: invalid("\\"),
+                span: Span::new(6, 12, 1, 7),
+            },
+            SyntaxError {
+                error_type: SyntaxErrorType::UnexpectedCharacter { found: '@' },
+                span: Span::new(14, 15, 2, 5),
+            },
         ]
     );
+    assert_eq!(tokens.last().unwrap().span, Span::new(15, 15, 2, 6));
 }
 
 #[test]
@@ -29,13 +131,13 @@ fn scan_number_test() {
     let scanner = Scanner::new(source.to_string());
     let tokens = scanner.scan_source().unwrap();
     assert_eq!(
-        tokens,
+        types_and_lines(tokens),
         vec![
-            Token::new(TokenType::Number(12345.0), 2),
-            Token::new(TokenType::Semicolon, 2),
-            Token::new(TokenType::Number(123.456), 3),
-            Token::new(TokenType::Semicolon, 3),
-            Token::new(TokenType::EOF, 4),
+            (TokenType::Number(12345.0), 2),
+            (TokenType::Semicolon, 2),
+            (TokenType::Number(123.456), 3),
+            (TokenType::Semicolon, 3),
+            (TokenType::EOF, 4),
         ]
     );
 }
@@ -49,13 +151,13 @@ fn scan_comment_test() {
     let scanner = Scanner::new(source.to_string());
     let tokens = scanner.scan_source().unwrap();
     assert_eq!(
-        tokens,
+        types_and_lines(tokens),
         vec![
-            Token::new(TokenType::Number(5.0), 3),
-            Token::new(TokenType::Slash, 3),
-            Token::new(TokenType::Number(10.0), 3),
-            Token::new(TokenType::Semicolon, 3),
-            Token::new(TokenType::EOF, 4),
+            (TokenType::Number(5.0), 3),
+            (TokenType::Slash, 3),
+            (TokenType::Number(10.0), 3),
+            (TokenType::Semicolon, 3),
+            (TokenType::EOF, 4),
         ]
     );
 }
@@ -69,17 +171,17 @@ fn scan_identifier_test() {
     let scanner = Scanner::new(source.to_string());
     let tokens = scanner.scan_source().unwrap();
     assert_eq!(
-        tokens,
+        types_and_lines(tokens),
         vec![
-            Token::new(TokenType::Var, 2),
-            Token::new(TokenType::Identifier("test_var".to_string()), 2),
-            Token::new(TokenType::Equal, 2),
-            Token::new(TokenType::String("hello, world".to_string()), 2),
-            Token::new(TokenType::Semicolon, 2),
-            Token::new(TokenType::Print, 3),
-            Token::new(TokenType::Identifier("test_var".to_string()), 3),
-            Token::new(TokenType::Semicolon, 3),
-            Token::new(TokenType::EOF, 4),
+            (TokenType::Var, 2),
+            (TokenType::Identifier("test_var".to_string()), 2),
+            (TokenType::Equal, 2),
+            (TokenType::String("hello, world".to_string()), 2),
+            (TokenType::Semicolon, 2),
+            (TokenType::Print, 3),
+            (TokenType::Identifier("test_var".to_string()), 3),
+            (TokenType::Semicolon, 3),
+            (TokenType::EOF, 4),
         ]
     );
 }
@@ -90,100 +192,100 @@ fn scanner_test() {
     let scanner = Scanner::new(source.to_string());
     let tokens = scanner.scan_source().unwrap();
     assert_eq!(
-        tokens,
+        types_and_lines(tokens),
         vec![
-            Token::new(TokenType::Print, 2),
-            Token::new(TokenType::String("Hello, world!".to_string()), 2),
-            Token::new(TokenType::Semicolon, 2),
-            Token::new(TokenType::Identifier("divide".to_string()), 3),
-            Token::new(TokenType::Slash, 3),
-            Token::new(TokenType::Identifier("me".to_string()), 3),
-            Token::new(TokenType::Semicolon, 3),
-            Token::new(TokenType::Minus, 4),
-            Token::new(TokenType::Identifier("negateMe".to_string()), 4),
-            Token::new(TokenType::Semicolon, 4),
-            Token::new(TokenType::Identifier("lessThan".to_string()), 5),
-            Token::new(TokenType::LessEqual, 5),
-            Token::new(TokenType::Identifier("orEqual".to_string()), 5),
-            Token::new(TokenType::Semicolon, 5),
-            Token::new(TokenType::Identifier("greater".to_string()), 6),
-            Token::new(TokenType::Greater, 6),
-            Token::new(TokenType::Identifier("than".to_string()), 6),
-            Token::new(TokenType::Semicolon, 6),
-            Token::new(TokenType::Identifier("greaterThan".to_string()), 7),
-            Token::new(TokenType::GreaterEqual, 7),
-            Token::new(TokenType::Identifier("orEqual".to_string()), 7),
-            Token::new(TokenType::Semicolon, 7),
-            Token::new(TokenType::Number(123.0), 9),
-            Token::new(TokenType::EqualEqual, 9),
-            Token::new(TokenType::String("123".to_string()), 9),
-            Token::new(TokenType::Semicolon, 9),
-            Token::new(TokenType::Bang, 10),
-            Token::new(TokenType::False, 10),
-            Token::new(TokenType::Semicolon, 10),
-            Token::new(TokenType::True, 11),
-            Token::new(TokenType::And, 11),
-            Token::new(TokenType::False, 11),
-            Token::new(TokenType::Semicolon, 11),
-            Token::new(TokenType::True, 12),
-            Token::new(TokenType::Or, 12),
-            Token::new(TokenType::False, 12),
-            Token::new(TokenType::Semicolon, 12),
-            Token::new(TokenType::If, 14),
-            Token::new(TokenType::LeftParen, 14),
-            Token::new(TokenType::Identifier("condition".to_string()), 14),
-            Token::new(TokenType::RightParen, 14),
-            Token::new(TokenType::LeftBrace, 14),
-            Token::new(TokenType::Print, 15),
-            Token::new(TokenType::String("yes".to_string()), 15),
-            Token::new(TokenType::Semicolon, 15),
-            Token::new(TokenType::RightBrace, 16),
-            Token::new(TokenType::Else, 16),
-            Token::new(TokenType::LeftBrace, 16),
-            Token::new(TokenType::Print, 17),
-            Token::new(TokenType::String("no".to_string()), 17),
-            Token::new(TokenType::Semicolon, 17),
-            Token::new(TokenType::RightBrace, 18),
-            Token::new(TokenType::Fun, 20),
-            Token::new(TokenType::Identifier("printSum".to_string()), 20),
-            Token::new(TokenType::LeftParen, 20),
-            Token::new(TokenType::Identifier("a".to_string()), 20),
-            Token::new(TokenType::Comma, 20),
-            Token::new(TokenType::Identifier("b".to_string()), 20),
-            Token::new(TokenType::RightParen, 20),
-            Token::new(TokenType::LeftBrace, 20),
-            Token::new(TokenType::Print, 21),
-            Token::new(TokenType::Identifier("a".to_string()), 21),
-            Token::new(TokenType::Plus, 21),
-            Token::new(TokenType::Identifier("b".to_string()), 21),
-            Token::new(TokenType::Semicolon, 21),
-            Token::new(TokenType::RightBrace, 22),
-            Token::new(TokenType::Class, 24),
-            Token::new(TokenType::Identifier("Breakfast".to_string()), 24),
-            Token::new(TokenType::LeftBrace, 24),
-            Token::new(TokenType::Identifier("cook".to_string()), 25),
-            Token::new(TokenType::LeftParen, 25),
-            Token::new(TokenType::RightParen, 25),
-            Token::new(TokenType::LeftBrace, 25),
-            Token::new(TokenType::Print, 26),
-            Token::new(TokenType::String("Eggs a-fryin'!".to_string()), 26),
-            Token::new(TokenType::Semicolon, 26),
-            Token::new(TokenType::RightBrace, 27),
-            Token::new(TokenType::Identifier("serve".to_string()), 29),
-            Token::new(TokenType::LeftParen, 29),
-            Token::new(TokenType::Identifier("who".to_string()), 29),
-            Token::new(TokenType::RightParen, 29),
-            Token::new(TokenType::LeftBrace, 29),
-            Token::new(TokenType::Print, 30),
-            Token::new(TokenType::String("Enjoy your breakfast, ".to_string()), 30),
-            Token::new(TokenType::Plus, 30),
-            Token::new(TokenType::Identifier("who".to_string()), 30),
-            Token::new(TokenType::Plus, 30),
-            Token::new(TokenType::String(".".to_string()), 30),
-            Token::new(TokenType::Semicolon, 30),
-            Token::new(TokenType::RightBrace, 31),
-            Token::new(TokenType::RightBrace, 32),
-            Token::new(TokenType::EOF, 32),
+            (TokenType::Print, 2),
+            (TokenType::String("Hello, world!".to_string()), 2),
+            (TokenType::Semicolon, 2),
+            (TokenType::Identifier("divide".to_string()), 3),
+            (TokenType::Slash, 3),
+            (TokenType::Identifier("me".to_string()), 3),
+            (TokenType::Semicolon, 3),
+            (TokenType::Minus, 4),
+            (TokenType::Identifier("negateMe".to_string()), 4),
+            (TokenType::Semicolon, 4),
+            (TokenType::Identifier("lessThan".to_string()), 5),
+            (TokenType::LessEqual, 5),
+            (TokenType::Identifier("orEqual".to_string()), 5),
+            (TokenType::Semicolon, 5),
+            (TokenType::Identifier("greater".to_string()), 6),
+            (TokenType::Greater, 6),
+            (TokenType::Identifier("than".to_string()), 6),
+            (TokenType::Semicolon, 6),
+            (TokenType::Identifier("greaterThan".to_string()), 7),
+            (TokenType::GreaterEqual, 7),
+            (TokenType::Identifier("orEqual".to_string()), 7),
+            (TokenType::Semicolon, 7),
+            (TokenType::Number(123.0), 9),
+            (TokenType::EqualEqual, 9),
+            (TokenType::String("123".to_string()), 9),
+            (TokenType::Semicolon, 9),
+            (TokenType::Bang, 10),
+            (TokenType::False, 10),
+            (TokenType::Semicolon, 10),
+            (TokenType::True, 11),
+            (TokenType::And, 11),
+            (TokenType::False, 11),
+            (TokenType::Semicolon, 11),
+            (TokenType::True, 12),
+            (TokenType::Or, 12),
+            (TokenType::False, 12),
+            (TokenType::Semicolon, 12),
+            (TokenType::If, 14),
+            (TokenType::LeftParen, 14),
+            (TokenType::Identifier("condition".to_string()), 14),
+            (TokenType::RightParen, 14),
+            (TokenType::LeftBrace, 14),
+            (TokenType::Print, 15),
+            (TokenType::String("yes".to_string()), 15),
+            (TokenType::Semicolon, 15),
+            (TokenType::RightBrace, 16),
+            (TokenType::Else, 16),
+            (TokenType::LeftBrace, 16),
+            (TokenType::Print, 17),
+            (TokenType::String("no".to_string()), 17),
+            (TokenType::Semicolon, 17),
+            (TokenType::RightBrace, 18),
+            (TokenType::Fun, 20),
+            (TokenType::Identifier("printSum".to_string()), 20),
+            (TokenType::LeftParen, 20),
+            (TokenType::Identifier("a".to_string()), 20),
+            (TokenType::Comma, 20),
+            (TokenType::Identifier("b".to_string()), 20),
+            (TokenType::RightParen, 20),
+            (TokenType::LeftBrace, 20),
+            (TokenType::Print, 21),
+            (TokenType::Identifier("a".to_string()), 21),
+            (TokenType::Plus, 21),
+            (TokenType::Identifier("b".to_string()), 21),
+            (TokenType::Semicolon, 21),
+            (TokenType::RightBrace, 22),
+            (TokenType::Class, 24),
+            (TokenType::Identifier("Breakfast".to_string()), 24),
+            (TokenType::LeftBrace, 24),
+            (TokenType::Identifier("cook".to_string()), 25),
+            (TokenType::LeftParen, 25),
+            (TokenType::RightParen, 25),
+            (TokenType::LeftBrace, 25),
+            (TokenType::Print, 26),
+            (TokenType::String("Eggs a-fryin'!".to_string()), 26),
+            (TokenType::Semicolon, 26),
+            (TokenType::RightBrace, 27),
+            (TokenType::Identifier("serve".to_string()), 29),
+            (TokenType::LeftParen, 29),
+            (TokenType::Identifier("who".to_string()), 29),
+            (TokenType::RightParen, 29),
+            (TokenType::LeftBrace, 29),
+            (TokenType::Print, 30),
+            (TokenType::String("Enjoy your breakfast, ".to_string()), 30),
+            (TokenType::Plus, 30),
+            (TokenType::Identifier("who".to_string()), 30),
+            (TokenType::Plus, 30),
+            (TokenType::String(".".to_string()), 30),
+            (TokenType::Semicolon, 30),
+            (TokenType::RightBrace, 31),
+            (TokenType::RightBrace, 32),
+            (TokenType::EOF, 32),
         ],
     );
 }
@@ -195,5 +297,97 @@ fn sytnax_error_test() {
     "#;
     let scanner = Scanner::new(source.to_string());
     let errors = scanner.scan_source().err().unwrap();
-    assert_eq!(errors, vec![SyntaxError { line_num: 2 }]);
+    assert_eq!(
+        errors,
+        vec![LoxError::from(SyntaxError {
+            error_type: SyntaxErrorType::UnterminatedString,
+            span: Span::new(9, 15, 2, 9)
+        })]
+    );
+
+    let errors = Scanner::new("var a = 1 @ 2;".into())
+        .scan_source()
+        .unwrap_err();
+    assert_eq!(
+        errors,
+        vec![LoxError::from(SyntaxError {
+            error_type: SyntaxErrorType::UnexpectedCharacter { found: '@' },
+            span: Span::new(10, 11, 1, 11)
+        })]
+    );
+}
+
+#[test]
+fn scan_span_test() {
+    let source = "var x = \"hi\";\n  print x >= 10.5;";
+    let scanner = Scanner::new(source.to_string());
+    let spans = scanner
+        .scan_source()
+        .unwrap()
+        .into_iter()
+        .map(|t| t.span)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        spans,
+        vec![
+            Span::new(0, 3, 1, 1),
+            Span::new(4, 5, 1, 5),
+            Span::new(6, 7, 1, 7),
+            Span::new(8, 12, 1, 9),
+            Span::new(12, 13, 1, 13),
+            Span::new(16, 21, 2, 3),
+            Span::new(22, 23, 2, 9),
+            Span::new(24, 26, 2, 11),
+            Span::new(27, 31, 2, 14),
+            Span::new(31, 32, 2, 18),
+            Span::new(32, 32, 2, 19),
+        ]
+    );
+
+    // Columns count characters, and restart inside a multi-line string.
+    let source = "\"é\" + \"a\nbé\" + x;";
+    let spans = Scanner::new(source.to_string())
+        .scan_source()
+        .unwrap()
+        .into_iter()
+        .map(|t| t.span)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        spans,
+        vec![
+            Span::new(0, 4, 1, 1),
+            Span::new(5, 6, 1, 5),
+            Span::new(7, 14, 1, 7),
+            Span::new(15, 16, 2, 5),
+            Span::new(17, 18, 2, 7),
+            Span::new(18, 19, 2, 8),
+            Span::new(19, 19, 2, 9),
+        ]
+    );
+
+    // `EOF` of an empty source is still on the first line.
+    let tokens = Scanner::new(String::new()).scan_source().unwrap();
+    assert_eq!(tokens[0].span, Span::new(0, 0, 1, 1));
+}
+
+#[test]
+fn scan_tokens_test() {
+    let (tokens, errors) = Scanner::new("x = 1 @ \"open".into()).scan_tokens();
+    assert_eq!(
+        types_and_lines(tokens),
+        vec![
+            (TokenType::Identifier("x".into()), 1),
+            (TokenType::Equal, 1),
+            (TokenType::Number(1.0), 1),
+            (TokenType::EOF, 1),
+        ]
+    );
+    let error_types = errors.into_iter().map(|e| e.error_type).collect::<Vec<_>>();
+    assert_eq!(
+        error_types,
+        vec![
+            SyntaxErrorType::UnexpectedCharacter { found: '@' },
+            SyntaxErrorType::UnterminatedString,
+        ]
+    );
 }
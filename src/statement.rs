@@ -1,34 +1,127 @@
+use std::rc::Rc;
+
 use rlox_macros::Visitor;
 
-use crate::{expression::Expression, impl_from_inner};
+use crate::{
+    expression::{Expression, Var},
+    impl_from_inner,
+    span::Span,
+};
 
 #[derive(Debug, Visitor)]
 #[cfg_attr(test, derive(serde::Serialize))]
+#[allow(clippy::enum_variant_names)]
 pub enum Stmt {
-    ExprStmt(Expression),
-    PrintStmt(Expression),
+    ExprStmt(ExprStmt),
+    PrintStmt(PrintStmt),
     VarDecl(VarDecl),
+    FunDecl(Rc<FunDecl>),
+    ClassDecl(ClassDecl),
     BlockStmt(BlockStmt),
     IfStmt(IfStmt),
     WhileStmt(WhileStmt),
+    Return(ReturnStmt),
+    Break(BreakStmt),
+    Continue(ContinueStmt),
 }
 
+impl_from_inner!(ExprStmt, Stmt);
+impl_from_inner!(PrintStmt, Stmt);
 impl_from_inner!(VarDecl, Stmt);
+impl_from_inner!(ClassDecl, Stmt);
 impl_from_inner!(BlockStmt, Stmt);
 impl_from_inner!(IfStmt, Stmt);
 impl_from_inner!(WhileStmt, Stmt);
 
+impl From<FunDecl> for Stmt {
+    fn from(value: FunDecl) -> Self {
+        Self::FunDecl(Rc::new(value))
+    }
+}
+
+impl From<ReturnStmt> for Stmt {
+    fn from(value: ReturnStmt) -> Self {
+        Self::Return(value)
+    }
+}
+
+impl From<BreakStmt> for Stmt {
+    fn from(value: BreakStmt) -> Self {
+        Self::Break(value)
+    }
+}
+
+impl From<ContinueStmt> for Stmt {
+    fn from(value: ContinueStmt) -> Self {
+        Self::Continue(value)
+    }
+}
+
+impl Stmt {
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::ExprStmt(inner) => inner.span,
+            Stmt::PrintStmt(inner) => inner.span,
+            Stmt::VarDecl(inner) => inner.span,
+            Stmt::FunDecl(inner) => inner.span,
+            Stmt::ClassDecl(inner) => inner.span,
+            Stmt::BlockStmt(inner) => inner.span,
+            Stmt::IfStmt(inner) => inner.span,
+            Stmt::WhileStmt(inner) => inner.span,
+            Stmt::Return(inner) => inner.span,
+            Stmt::Break(inner) => inner.span,
+            Stmt::Continue(inner) => inner.span,
+        }
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct ExprStmt {
+    pub expr: Expression,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct PrintStmt {
+    pub expr: Expression,
+    pub span: Span,
+}
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(serde::Serialize))]
 pub struct VarDecl {
     pub name: String,
     pub initializer: Option<Box<Expression>>,
+    pub span: Span,
+}
+
+/// Function declarations are reference counted so that function values can
+/// outlive the statements they were parsed from (e.g. between REPL lines).
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct FunDecl {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Vec<Stmt>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct ClassDecl {
+    pub name: String,
+    pub superclass: Option<Var>,
+    pub methods: Vec<Rc<FunDecl>>,
+    pub span: Span,
 }
 
 #[derive(Debug)]
 #[cfg_attr(test, derive(serde::Serialize))]
 pub struct BlockStmt {
     pub stmts: Vec<Stmt>,
+    pub span: Span,
 }
 
 #[derive(Debug)]
@@ -37,6 +130,7 @@ pub struct IfStmt {
     pub condition: Box<Expression>,
     pub then_branch: Box<Stmt>,
     pub else_branch: Option<Box<Stmt>>,
+    pub span: Span,
 }
 
 #[derive(Debug)]
@@ -44,4 +138,31 @@ pub struct IfStmt {
 pub struct WhileStmt {
     pub condition: Box<Expression>,
     pub body: Box<Stmt>,
+    /// The increment clause of a `for` loop, run after the body even when it
+    /// ends with `continue`.
+    pub increment: Option<Box<Expression>>,
+    pub label: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct ReturnStmt {
+    pub value: Option<Box<Expression>>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct BreakStmt {
+    /// The loop to leave, or the innermost one if there is no label.
+    pub label: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct ContinueStmt {
+    pub label: Option<String>,
+    pub span: Span,
 }
@@ -2,22 +2,17 @@ use std::{fmt::Display, hash::Hash, sync::LazyLock, vec::IntoIter};
 
 use rustc_hash::FxHashMap;
 
+use crate::span::Span;
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Token {
     pub token_type: TokenType,
-    pub line_num: u32,
+    pub span: Span,
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, line_num: u32) -> Self {
-        Token {
-            token_type,
-            line_num,
-        }
-    }
-
-    pub fn lexeme(&self) -> String {
-        self.token_type.lexeme()
+    pub fn new(token_type: TokenType, span: Span) -> Self {
+        Token { token_type, span }
     }
 }
 
@@ -46,12 +41,16 @@ impl Iterator for Tokens {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum TokenType {
     // Single-character tokens.
     LeftParen,
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
     Comma,
     Dot,
     Minus,
@@ -73,11 +72,16 @@ pub enum TokenType {
     // Literals.
     Identifier(String),
     String(String),
+    /// A string segment ending in `${`, followed by the tokens of the
+    /// interpolated expression, a `}`, and the rest of the string.
+    Interpolation(String),
     Number(f64),
 
     // Keywords.
     And,
+    Break,
     Class,
+    Continue,
     Else,
     False,
     Fun,
@@ -106,9 +110,15 @@ impl Hash for TokenType {
 }
 
 impl TokenType {
+    pub fn is_keyword(&self) -> bool {
+        KEYWORDS.values().any(|keyword| keyword == self)
+    }
+
     pub fn lexeme(&self) -> String {
         match &self {
-            TokenType::Identifier(inner) | TokenType::String(inner) => inner.clone(),
+            TokenType::Identifier(inner)
+            | TokenType::String(inner)
+            | TokenType::Interpolation(inner) => inner.clone(),
             TokenType::Number(inner) => format!("{}", inner),
             _ => match LEXEMES.get(self) {
                 Some(s) => s.to_string(),
@@ -121,7 +131,9 @@ impl TokenType {
 pub static KEYWORDS: LazyLock<FxHashMap<&str, TokenType>> = LazyLock::new(|| {
     FxHashMap::from_iter([
         ("and", TokenType::And),
+        ("break", TokenType::Break),
         ("class", TokenType::Class),
+        ("continue", TokenType::Continue),
         ("else", TokenType::Else),
         ("false", TokenType::False),
         ("fun", TokenType::Fun),
@@ -146,6 +158,9 @@ static LEXEMES: LazyLock<FxHashMap<TokenType, &str>> = LazyLock::new(|| {
         (TokenType::RightParen, ")"),
         (TokenType::LeftBrace, "{"),
         (TokenType::RightBrace, "}"),
+        (TokenType::LeftBracket, "["),
+        (TokenType::RightBracket, "]"),
+        (TokenType::Colon, ":"),
         (TokenType::Comma, ","),
         (TokenType::Dot, "."),
         (TokenType::Minus, "-"),
@@ -164,7 +179,9 @@ static LEXEMES: LazyLock<FxHashMap<TokenType, &str>> = LazyLock::new(|| {
         (TokenType::LessEqual, "<="),
         // Keywords
         (TokenType::And, "and"),
+        (TokenType::Break, "break"),
         (TokenType::Class, "class"),
+        (TokenType::Continue, "continue"),
         (TokenType::Else, "else"),
         (TokenType::False, "false"),
         (TokenType::Fun, "fun"),
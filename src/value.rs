@@ -1,22 +1,329 @@
 use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::BTreeMap,
     fmt::Display,
+    hash::{Hash, Hasher},
     ops::{Add, Div, Mul, Sub},
+    rc::Rc,
 };
 
-use anyhow::{bail, Result};
+use crate::{
+    bytecode::{BoundMethod, Closure},
+    class::{Class, Instance},
+    error::RuntimeErrorType,
+    function::Function,
+    native::NativeFunction,
+};
 
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
+#[derive(Debug, Clone)]
 pub enum Value {
     String(String),
     Number(f64),
     Bool(bool),
+    /// Lists are shared, so changes through one reference show up in all.
+    List(Rc<RefCell<Vec<Value>>>),
+    /// Shared like lists. Entries are kept sorted by key, which makes
+    /// iteration order predictable.
+    Map(Rc<RefCell<BTreeMap<MapKey, Value>>>),
+    Function(Rc<Function>),
+    Class(Rc<Class>),
+    Instance(Rc<Instance>),
+    Closure(Rc<Closure>),
+    BoundMethod(Rc<BoundMethod>),
+    Native(Rc<NativeFunction>),
     Nil,
 }
 
+/// Collections compare by contents, except that one always equals itself.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        equal(self, other, &mut Vec::new())
+    }
+}
+
+/// Compares two values. `path` holds the pairs of collections being
+/// compared further out, and meeting a pair again counts as equal, so
+/// comparing collections that contain themselves does not recurse forever.
+fn equal(l: &Value, r: &Value, path: &mut Vec<(*const (), *const ())>) -> bool {
+    match (l, r) {
+        (Value::String(l), Value::String(r)) => l == r,
+        (Value::Number(l), Value::Number(r)) => l == r,
+        (Value::Bool(l), Value::Bool(r)) => l == r,
+        (Value::List(l), Value::List(r)) => {
+            let pair = (Rc::as_ptr(l) as *const (), Rc::as_ptr(r) as *const ());
+            if Rc::ptr_eq(l, r) || path.contains(&pair) {
+                return true;
+            }
+            path.push(pair);
+            let (l, r) = (l.borrow(), r.borrow());
+            let equal =
+                l.len() == r.len() && l.iter().zip(r.iter()).all(|(l, r)| equal(l, r, path));
+            path.pop();
+            equal
+        }
+        (Value::Map(l), Value::Map(r)) => {
+            let pair = (Rc::as_ptr(l) as *const (), Rc::as_ptr(r) as *const ());
+            if Rc::ptr_eq(l, r) || path.contains(&pair) {
+                return true;
+            }
+            path.push(pair);
+            let (l, r) = (l.borrow(), r.borrow());
+            let equal = l.len() == r.len()
+                && l.iter()
+                    .zip(r.iter())
+                    .all(|((lk, lv), (rk, rv))| lk == rk && equal(lv, rv, path));
+            path.pop();
+            equal
+        }
+        (Value::Function(l), Value::Function(r)) => l == r,
+        (Value::Class(l), Value::Class(r)) => l == r,
+        (Value::Instance(l), Value::Instance(r)) => l == r,
+        (Value::Closure(l), Value::Closure(r)) => l == r,
+        (Value::BoundMethod(l), Value::BoundMethod(r)) => l == r,
+        (Value::Native(l), Value::Native(r)) => l == r,
+        (Value::Nil, Value::Nil) => true,
+        _ => false,
+    }
+}
+
+/// Only numbers and strings are ordered; collections in particular are not,
+/// so comparing them never looks at their contents.
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Number(l), Value::Number(r)) => l.partial_cmp(r),
+            (Value::String(l), Value::String(r)) => l.partial_cmp(r),
+            _ => None,
+        }
+    }
+}
+
 impl Value {
     pub fn is_truthy(&self) -> bool {
         !matches!(self, Value::Bool(false) | Value::Nil)
     }
+
+    /// Applies a comparison operator such as [`PartialOrd::lt`], failing
+    /// unless both operands are numbers or both are strings.
+    pub fn compare(
+        &self,
+        other: &Value,
+        op: fn(&Value, &Value) -> bool,
+    ) -> Result<Value, RuntimeErrorType> {
+        match (self, other) {
+            (Value::Number(_), Value::Number(_)) | (Value::String(_), Value::String(_)) => {
+                Ok(Value::Bool(op(self, other)))
+            }
+            (l, r) => Err(expect_numbers(l, r)),
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Number(_) => "number",
+            Value::Bool(_) => "bool",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+            Value::Function(_) | Value::Closure(_) | Value::BoundMethod(_) | Value::Native(_) => {
+                "function"
+            }
+            Value::Class(_) => "class",
+            Value::Instance(_) => "instance",
+            Value::Nil => "nil",
+        }
+    }
+
+    pub fn list(values: Vec<Value>) -> Self {
+        Value::List(Rc::new(RefCell::new(values)))
+    }
+
+    pub fn map(entries: BTreeMap<MapKey, Value>) -> Self {
+        Value::Map(Rc::new(RefCell::new(entries)))
+    }
+
+    /// Evaluates `self[index]`.
+    pub fn get_index(&self, index: &Value) -> Result<Value, RuntimeErrorType> {
+        match self {
+            Value::List(list) => {
+                let list = list.borrow();
+                let index = checked_index(index, list.len())?;
+                Ok(list[index].clone())
+            }
+            Value::Map(map) => {
+                let key = MapKey::try_from(index)?;
+                match map.borrow().get(&key) {
+                    Some(value) => Ok(value.clone()),
+                    None => Err(RuntimeErrorType::UndefinedKey {
+                        key: key.to_string(),
+                    }),
+                }
+            }
+            value => Err(mismatch("list or map", value)),
+        }
+    }
+
+    /// Evaluates `self[index] = value`.
+    pub fn set_index(&self, index: &Value, value: Value) -> Result<(), RuntimeErrorType> {
+        match self {
+            Value::List(list) => {
+                let mut list = list.borrow_mut();
+                let index = checked_index(index, list.len())?;
+                list[index] = value;
+                Ok(())
+            }
+            Value::Map(map) => {
+                map.borrow_mut().insert(MapKey::try_from(index)?, value);
+                Ok(())
+            }
+            target => Err(mismatch("list or map", target)),
+        }
+    }
+}
+
+/// A value usable as a map key: `nil`, a bool, a number or a string.
+///
+/// Keys are compared by value. For numbers `-0` and `0` are the same key,
+/// and so are all NaNs, so that every key can be looked up again.
+#[derive(Debug, Clone)]
+pub enum MapKey {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl MapKey {
+    fn number(n: f64) -> Self {
+        // Every NaN is the same key, and so are `0` and `-0`.
+        match n {
+            n if n.is_nan() => MapKey::Number(f64::NAN),
+            0.0 => MapKey::Number(0.0),
+            n => MapKey::Number(n),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            MapKey::Nil => 0,
+            MapKey::Bool(_) => 1,
+            MapKey::Number(_) => 2,
+            MapKey::String(_) => 3,
+        }
+    }
+}
+
+impl TryFrom<&Value> for MapKey {
+    type Error = RuntimeErrorType;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Nil => Ok(MapKey::Nil),
+            Value::Bool(b) => Ok(MapKey::Bool(*b)),
+            Value::Number(n) => Ok(MapKey::number(*n)),
+            Value::String(s) => Ok(MapKey::String(s.clone())),
+            value => Err(RuntimeErrorType::InvalidKey {
+                actual: value.type_name(),
+            }),
+        }
+    }
+}
+
+impl From<MapKey> for Value {
+    fn from(key: MapKey) -> Self {
+        match key {
+            MapKey::Nil => Value::Nil,
+            MapKey::Bool(b) => Value::Bool(b),
+            MapKey::Number(n) => Value::Number(n),
+            MapKey::String(s) => Value::String(s),
+        }
+    }
+}
+
+// Numbers are normalized on the way in, which keeps `total_cmp` and hashing
+// their bits consistent with this.
+impl PartialEq for MapKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for MapKey {}
+
+impl Hash for MapKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self {
+            MapKey::Nil => {}
+            MapKey::Bool(b) => b.hash(state),
+            MapKey::Number(n) => n.to_bits().hash(state),
+            MapKey::String(s) => s.hash(state),
+        }
+    }
+}
+
+/// `nil` sorts first, then bools, numbers and strings.
+impl Ord for MapKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (MapKey::Bool(l), MapKey::Bool(r)) => l.cmp(r),
+            (MapKey::Number(l), MapKey::Number(r)) => l.total_cmp(r),
+            (MapKey::String(l), MapKey::String(r)) => l.cmp(r),
+            (l, r) => l.rank().cmp(&r.rank()),
+        }
+    }
+}
+
+impl PartialOrd for MapKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Strings are quoted, as they are inside collections.
+impl Display for MapKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapKey::String(s) => write!(f, "\"{}\"", s),
+            key => write!(f, "{}", Value::from(key.clone())),
+        }
+    }
+}
+
+/// Converts `index` to a position in a sequence of length `len`.
+fn checked_index(index: &Value, len: usize) -> Result<usize, RuntimeErrorType> {
+    let index = whole_index(index)?;
+    if index >= len {
+        return Err(RuntimeErrorType::IndexOutOfRange { index, len });
+    }
+    Ok(index)
+}
+
+/// Converts `index` to a `usize`, without checking it against any bounds.
+pub(crate) fn whole_index(index: &Value) -> Result<usize, RuntimeErrorType> {
+    match *index {
+        Value::Number(n) if n < 0.0 => Err(RuntimeErrorType::NegativeIndex { index: n }),
+        Value::Number(n) if n.fract() != 0.0 => Err(RuntimeErrorType::FractionalIndex { index: n }),
+        Value::Number(n) => Ok(n as usize),
+        ref index => Err(mismatch("number", index)),
+    }
+}
+
+/// Builds the error for an operand that is not of the `expected` type.
+fn mismatch(expected: &'static str, actual: &Value) -> RuntimeErrorType {
+    RuntimeErrorType::TypeMismatch {
+        expected,
+        actual: actual.type_name(),
+    }
+}
+
+/// Picks whichever operand is not a number to report as the mismatch.
+fn expect_numbers(l: &Value, r: &Value) -> RuntimeErrorType {
+    match l {
+        Value::Number(_) => mismatch("number", r),
+        _ => mismatch("number", l),
+    }
 }
 
 impl Display for Value {
@@ -26,54 +333,106 @@ impl Display for Value {
             Value::String(s) => s,
             Value::Number(n) => &format!("{}", n),
             Value::Bool(b) => &format!("{}", b),
+            Value::List(_) | Value::Map(_) => &collection(self, &mut Vec::new()),
+            Value::Function(fun) => &format!("<fn {}>", fun.name()),
+            Value::Class(class) => &class.name,
+            Value::Instance(instance) => &format!("{} instance", instance.class.name),
+            Value::Closure(closure) => &format!("<fn {}>", closure.name()),
+            Value::BoundMethod(bound) => &format!("<fn {}>", bound.method.name()),
+            Value::Native(native) => &format!("<native fn {}>", native.name),
         };
         write!(f, "{}", repr)
     }
 }
 
+/// Formats a list or map. `path` holds the collections it is nested in, and
+/// a collection found inside itself is shown as `[...]` or `{...}`.
+fn collection(value: &Value, path: &mut Vec<*const ()>) -> String {
+    let (id, recurring) = match value {
+        Value::List(list) => (Rc::as_ptr(list) as *const (), "[...]"),
+        Value::Map(map) => (Rc::as_ptr(map) as *const (), "{...}"),
+        value => return value.to_string(),
+    };
+    if path.contains(&id) {
+        return recurring.to_string();
+    }
+    path.push(id);
+    let repr = match value {
+        Value::List(list) => {
+            let elements = list
+                .borrow()
+                .iter()
+                .map(|value| element(value, path))
+                .collect::<Vec<_>>();
+            format!("[{}]", elements.join(", "))
+        }
+        Value::Map(map) => {
+            let entries = map
+                .borrow()
+                .iter()
+                .map(|(key, value)| format!("{}: {}", key, element(value, path)))
+                .collect::<Vec<_>>();
+            format!("{{{}}}", entries.join(", "))
+        }
+        _ => unreachable!(),
+    };
+    path.pop();
+    repr
+}
+
+/// Formats a value inside a collection, where strings are quoted.
+fn element(value: &Value, path: &mut Vec<*const ()>) -> String {
+    match value {
+        Value::String(s) => format!("\"{}\"", s),
+        value => collection(value, path),
+    }
+}
+
 impl Sub for Value {
-    type Output = Result<Self>;
+    type Output = Result<Self, RuntimeErrorType>;
     fn sub(self, rhs: Self) -> Self::Output {
         let result = match (self, rhs) {
             (Self::Number(l), Self::Number(r)) => Self::Number(l - r),
-            (l, r) => bail!("Cannot subtract {:?} from {:?}", r, l),
+            (l, r) => return Err(expect_numbers(&l, &r)),
         };
         Ok(result)
     }
 }
 
 impl Add for Value {
-    type Output = Result<Self>;
+    type Output = Result<Self, RuntimeErrorType>;
     fn add(self, rhs: Self) -> Self::Output {
         let result = match (self, rhs) {
             (Self::Number(l), Self::Number(r)) => Self::Number(l + r),
             (Self::String(l), Self::String(r)) => Self::String(l + &r),
             (Self::Number(l), Self::String(r)) => Self::String(format!("{}", l) + &r),
             (Self::String(l), Self::Number(r)) => Self::String(l + &format!("{}", r)),
-            (l, r) => bail!("Cannot add {:?} and {:?}", l, r),
+            (Self::Number(_) | Self::String(_), r) => return Err(mismatch("number or string", &r)),
+            (l, _) => return Err(mismatch("number or string", &l)),
         };
         Ok(result)
     }
 }
 
 impl Mul for Value {
-    type Output = Result<Self>;
+    type Output = Result<Self, RuntimeErrorType>;
     fn mul(self, rhs: Self) -> Self::Output {
         let result = match (self, rhs) {
             (Self::Number(l), Self::Number(r)) => Self::Number(l * r),
             (Self::String(s), Self::Number(n)) => Self::String(s.repeat(n as usize)),
-            (l, r) => bail!("Cannot multiply {:?} and {:?}", l, r),
+            (Self::String(_), r) => return Err(mismatch("number", &r)),
+            (l, r) => return Err(expect_numbers(&l, &r)),
         };
         Ok(result)
     }
 }
 
 impl Div for Value {
-    type Output = Result<Self>;
+    type Output = Result<Self, RuntimeErrorType>;
     fn div(self, rhs: Self) -> Self::Output {
         let result = match (self, rhs) {
             (Self::Number(l), Self::Number(r)) => Self::Number(l / r),
-            (l, r) => bail!("Cannot multiply {:?} and {:?}", l, r),
+            (l, r) => return Err(expect_numbers(&l, &r)),
         };
         Ok(result)
     }
@@ -0,0 +1,68 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+fn rlox(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_eval() {
+    let output = rlox(&["-e", "print 1 + 2;"], "");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3\n");
+
+    let output = rlox(&["--vm", "--eval", "print 1 + 2;"], "");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3\n");
+}
+
+#[test]
+fn test_script_file() {
+    let path = std::env::temp_dir().join("rlox_test_script_file.lox");
+    std::fs::write(&path, "var a = \"file\";\nprint a;\n").unwrap();
+    let output = rlox(&[path.to_str().unwrap()], "");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "file\n");
+
+    let output = rlox(&["missing.lox"], "");
+    assert_eq!(output.status.code(), Some(66));
+}
+
+#[test]
+fn test_stdin() {
+    let output = rlox(&[], "print \"piped\";\n");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "piped\n");
+}
+
+#[test]
+fn test_exit_codes() {
+    assert_eq!(rlox(&["-e", "print ;"], "").status.code(), Some(65));
+    assert_eq!(rlox(&["-e", "return 1;"], "").status.code(), Some(65));
+    assert_eq!(rlox(&["-e", "print x;"], "").status.code(), Some(70));
+    assert_eq!(rlox(&["--vm", "-e", "-nil;"], "").status.code(), Some(70));
+    assert_eq!(rlox(&["-e"], "").status.code(), Some(64));
+}
+
+#[test]
+fn test_runtime_error_stops_script() {
+    let output = rlox(&["-e", "print 1; print nil + 1; print 2;"], "");
+    assert_eq!(output.status.code(), Some(70));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("error: Expected number"));
+}